@@ -1,3 +1,3 @@
 #!/bin/sh
 #Create subset of stars (those with common names) from main dataset
-xsv search -i -s proper '[.*\S.*]' hygdata_v3.csv | xsv select proper,x,y,z,dist,ra,dec > star_data.csv
\ No newline at end of file
+xsv search -i -s proper '[.*\S.*]' hygdata_v3.csv | xsv select id,proper,x,y,z,dist,ra,dec,hip,hd,hr,gl,bf,bayer,flam,con,mag,absmag,ci,spect,lum,pmra,pmdec,rv > star_data.csv
@@ -0,0 +1,135 @@
+use super::*;
+use coords::*;
+use error::Error;
+use star::StarData;
+use std::fmt;
+use std::str::FromStr;
+use time::JulianDate;
+use vsop87::vsop87c;
+
+/// A solar system body or a star from the catalogue
+///
+/// # Example
+///
+/// ```
+/// use astral::Body;
+/// let body: Body = "jupiter".parse().unwrap();
+/// assert_eq!(body, Body::Jupiter);
+/// assert_eq!(body.to_string(), "Jupiter");
+/// assert_eq!(body.position(2458061.5).julian_day, 2458061.5);
+///
+/// let star: Body = "Polaris".parse().unwrap();
+/// assert_eq!(star.to_string(), "Polaris");
+/// ```
+#[derive(Serialize,Deserialize,Debug, Clone, PartialEq)]
+pub enum Body {
+    Sun,
+    Moon,
+    Mercury,
+    Venus,
+    Mars,
+    Jupiter,
+    Saturn,
+    Uranus,
+    Neptune,
+    Star(Box<StarData>),
+}
+
+pub const PLANETS: [Body; 7] = [
+    Body::Mercury,
+    Body::Venus,
+    Body::Mars,
+    Body::Jupiter,
+    Body::Saturn,
+    Body::Uranus,
+    Body::Neptune,
+];
+
+impl Body {
+    /// Returns the geocentric position of the body at the given Julian day, UTC unless
+    /// another time scale is given
+    pub fn position<J: Into<JulianDate>>(&self, julian: J) -> CelestialPosition {
+        match *self {
+            Body::Sun => sun::get_celestial_position(julian),
+            Body::Moon => moon::get_celestial_position(julian),
+            Body::Star(ref star_data) => star_data.get_celestial_position(julian),
+            _ => CelestialPosition::from_helio_fn(
+                julian,
+                |jd| self.helio_cart(jd).unwrap(),
+                CelestialBodyType::Planet,
+            ),
+        }
+    }
+
+    /// Heliocentric VSOP87C coordinates (AU, ecliptic of date) for the planets at a
+    /// Julian day of TDB
+    pub fn helio_cart(&self, julian: f64) -> Option<CartesianCoordinates> {
+        let helio_coords = match *self {
+            Body::Mercury => vsop87c::mercury(julian),
+            Body::Venus => vsop87c::venus(julian),
+            Body::Mars => vsop87c::mars(julian),
+            Body::Jupiter => vsop87c::jupiter(julian),
+            Body::Saturn => vsop87c::saturn(julian),
+            Body::Uranus => vsop87c::uranus(julian),
+            Body::Neptune => vsop87c::neptune(julian),
+            _ => return None,
+        };
+        Some(CartesianCoordinates {
+            x: helio_coords.x,
+            y: helio_coords.y,
+            z: helio_coords.z,
+        })
+    }
+
+    pub fn is_planet(&self) -> bool {
+        !matches!(*self, Body::Sun | Body::Moon | Body::Star(_))
+    }
+
+    pub fn object_type(&self) -> CelestialBodyType {
+        match *self {
+            Body::Sun => CelestialBodyType::Sun,
+            Body::Moon => CelestialBodyType::Moon,
+            Body::Star(_) => CelestialBodyType::Star,
+            _ => CelestialBodyType::Planet,
+        }
+    }
+}
+
+impl fmt::Display for Body {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match *self {
+            Body::Sun => "Sun",
+            Body::Moon => "Moon",
+            Body::Mercury => "Mercury",
+            Body::Venus => "Venus",
+            Body::Mars => "Mars",
+            Body::Jupiter => "Jupiter",
+            Body::Saturn => "Saturn",
+            Body::Uranus => "Uranus",
+            Body::Neptune => "Neptune",
+            Body::Star(ref star_data) => return write!(f, "{}", star_data.name()),
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for Body {
+    type Err = Error;
+
+    /// Parses a case-insensitive body name, falling back to the star catalogue
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        let body = match name.to_lowercase().as_str() {
+            "sun" => Body::Sun,
+            "moon" => Body::Moon,
+            "mercury" => Body::Mercury,
+            "venus" => Body::Venus,
+            "mars" => Body::Mars,
+            "jupiter" => Body::Jupiter,
+            "saturn" => Body::Saturn,
+            "uranus" => Body::Uranus,
+            "neptune" => Body::Neptune,
+            _ => Body::Star(Box::new(star::get_data(name.to_string())?)),
+        };
+        Ok(body)
+    }
+}
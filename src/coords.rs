@@ -1,22 +1,257 @@
 use super::*;
+use precession::Equinox;
+use frames::{Frame, Spherical};
+use time::JulianDate;
+use units::{Degrees, HourAngle, Radians};
+use locale::MoonPhaseLocale;
+use std::fmt;
 #[derive(Serialize,Deserialize,Debug, Clone, Copy, PartialEq)]
 pub struct Location {
     pub lat: f64,
     pub lon: f64,
+    /// Height above sea level in meters
+    #[serde(default)]
+    pub elevation: f64,
+    /// Atmospheric pressure in millibars
+    #[serde(default = "default_pressure")]
+    pub pressure: f64,
+    /// Air temperature in degrees Celsius
+    #[serde(default = "default_temperature")]
+    pub temperature: f64,
+}
+
+fn default_pressure() -> f64 {
+    util::STANDARD_PRESSURE
+}
+
+fn default_temperature() -> f64 {
+    util::STANDARD_TEMPERATURE
+}
+
+/// The point at 0° latitude and longitude, at sea level under the standard atmosphere, so
+/// that a location can be written as a struct literal without spelling out every field
+///
+/// # Example
+///
+/// ```
+/// use astral::coords::Location;
+/// let location = Location { lat: 38.44043, lon: -122.71405, ..Default::default() };
+/// assert_eq!(location, Location::new(38.44043, -122.71405).unwrap());
+/// ```
+impl Default for Location {
+    fn default() -> Location {
+        Location {
+            lat: 0.0,
+            lon: 0.0,
+            elevation: 0.0,
+            pressure: util::STANDARD_PRESSURE,
+            temperature: util::STANDARD_TEMPERATURE,
+        }
+    }
+}
+
+impl Location {
+    /// Returns a Location after checking that `lat` and `lon` are valid degrees
+    pub fn new(lat: f64, lon: f64) -> Result<Location, Error> {
+        if !(-90.0..=90.0).contains(&lat) || !(-180.0..=180.0).contains(&lon) {
+            return Err(Error::InvalidLocation { lat, lon });
+        }
+        Ok(Location {
+            lat,
+            lon,
+            elevation: 0.0,
+            pressure: util::STANDARD_PRESSURE,
+            temperature: util::STANDARD_TEMPERATURE,
+        })
+    }
+    pub fn with_elevation(mut self, elevation: f64) -> Location {
+        self.elevation = elevation;
+        self
+    }
+    pub fn with_pressure(mut self, pressure: f64) -> Location {
+        self.pressure = pressure;
+        self
+    }
+    pub fn with_temperature(mut self, temperature: f64) -> Location {
+        self.temperature = temperature;
+        self
+    }
+    /// Refraction (radians) for a true altitude in radians, under this location's atmosphere
+    pub fn refraction(&self, alt: f64) -> f64 {
+        util::refraction(alt, self.pressure, self.temperature)
+    }
+    /// How much lower than the astronomical horizon (degrees) the sea horizon appears
+    /// from this location's elevation
+    pub fn horizon_dip(&self) -> f64 {
+        util::horizon_dip(self.elevation)
+    }
+    /// Refraction relative to the standard atmosphere (1010 mbar, 10°C)
+    pub fn refraction_scale(&self) -> f64 {
+        util::refraction_scale(self.pressure, self.temperature)
+    }
 }
 #[derive(Debug, Clone)]
 pub struct LunarInfo {
-    pub altitude: f64,
-    pub azimuth: f64,
+    pub altitude: Degrees,
+    pub azimuth: Degrees,
     pub percent_illuminated: f64,
-    pub phase_name: String,
+    pub phase: MoonPhase,
     pub phase_image: String,
 }
 
+/// The eight named phases of the Moon
+#[derive(Serialize,Deserialize,Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MoonPhase {
+    New,
+    WaxingCrescent,
+    FirstQuarter,
+    WaxingGibbous,
+    Full,
+    WaningGibbous,
+    LastQuarter,
+    WaningCrescent,
+}
+
+impl MoonPhase {
+    /// Names the phase for a fraction of the lunation as returned by `moon::getMoonPhase`
+    /// (0 new, 0.25 first quarter, 0.5 full, 0.75 last quarter). Each name covers an
+    /// eighth of the cycle centred on its principal phase.
+    pub fn from_phase(moon_phase: f64) -> MoonPhase {
+        let phase = moon_phase - moon_phase.floor();
+        let octant = ((phase * 8.0 + 0.5).floor() as usize) % 8;
+        [
+            MoonPhase::New,
+            MoonPhase::WaxingCrescent,
+            MoonPhase::FirstQuarter,
+            MoonPhase::WaxingGibbous,
+            MoonPhase::Full,
+            MoonPhase::WaningGibbous,
+            MoonPhase::LastQuarter,
+            MoonPhase::WaningCrescent,
+        ][octant]
+    }
+
+    /// Sun-Moon elongation in ecliptic longitude at the principal phases
+    pub fn elongation(&self) -> Option<Degrees> {
+        match *self {
+            MoonPhase::New => Some(Degrees(0.0)),
+            MoonPhase::FirstQuarter => Some(Degrees(90.0)),
+            MoonPhase::Full => Some(Degrees(180.0)),
+            MoonPhase::LastQuarter => Some(Degrees(270.0)),
+            _ => None,
+        }
+    }
+
+    /// English name, as used by `Display`
+    pub fn name(&self) -> &'static str {
+        locale::ENGLISH.0[*self as usize]
+    }
+
+    /// Name of the phase in another language
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use astral::coords::MoonPhase;
+    /// use astral::locale;
+    /// assert_eq!(MoonPhase::Full.to_string(), "full");
+    /// assert_eq!(MoonPhase::Full.localized_name(&locale::GERMAN), "Vollmond");
+    /// assert_eq!(MoonPhase::FirstQuarter.localized_name(&locale::SPANISH), "cuarto creciente");
+    /// ```
+    pub fn localized_name<'a, L: MoonPhaseLocale + ?Sized>(&self, locale: &'a L) -> &'a str {
+        locale.phase_name(*self)
+    }
+}
+
+impl fmt::Display for MoonPhase {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// Instant of a principal phase of the Moon
+#[derive(Serialize,Deserialize,Debug, Clone, Copy, PartialEq)]
+pub struct PhaseEvent {
+    pub phase: MoonPhase,
+    pub julian_day: f64,
+}
+
+/// Moonrise, moonset and upper transit within a 24 hour window, as Julian days
+#[derive(Serialize,Deserialize,Debug, Clone, Copy, PartialEq)]
+pub struct MoonTimes {
+    pub rise: Option<f64>,
+    pub set: Option<f64>,
+    pub transit: Option<f64>,
+    /// The Moon stays above the horizon for the whole window
+    pub always_up: bool,
+    /// The Moon stays below the horizon for the whole window
+    pub always_down: bool,
+}
+
+#[derive(Serialize,Deserialize,Debug, Clone, Copy, PartialEq)]
+pub enum HorizonEventKind {
+    Rise,
+    Culmination,
+    Set,
+}
+
+/// A crossing of the target altitude, or an upper culmination, found by `rise_set::find_events`
+#[derive(Serialize,Deserialize,Debug, Clone, Copy, PartialEq)]
+pub struct HorizonEvent {
+    pub kind: HorizonEventKind,
+    pub julian_day: f64,
+    /// Azimuth measured from north through east, within [0, 360)
+    pub az: Degrees,
+    /// Geometric altitude
+    pub alt: Degrees,
+}
+
+/// Outcome of a rise/set search for a given altitude
+#[derive(Serialize,Deserialize,Debug, Clone, Copy, PartialEq)]
+pub enum RiseSet {
+    /// Julian days at which the body crosses the altitude going up and going down
+    Times { rise: f64, set: f64 },
+    /// The body stays above the altitude all day (e.g. midnight sun)
+    AlwaysUp,
+    /// The body stays below the altitude all day (e.g. polar night)
+    AlwaysDown,
+}
+
+impl RiseSet {
+    pub fn rise(&self) -> Option<f64> {
+        match *self {
+            RiseSet::Times { rise, .. } => Some(rise),
+            _ => None,
+        }
+    }
+    pub fn set(&self) -> Option<f64> {
+        match *self {
+            RiseSet::Times { set, .. } => Some(set),
+            _ => None,
+        }
+    }
+}
+
+/// Sun event times for one day, as Julian days. Use `util::from_julian` for datetimes.
+#[derive(Serialize,Deserialize,Debug, Clone, Copy, PartialEq)]
+pub struct SunTimes {
+    pub solar_noon: f64,
+    pub nadir: f64,
+    /// Sunrise and sunset (sun's upper limb on the horizon, -0.833°)
+    pub sun: RiseSet,
+    /// Civil dawn and dusk (-6°)
+    pub civil: RiseSet,
+    /// Nautical dawn and dusk (-12°)
+    pub nautical: RiseSet,
+    /// Astronomical dawn and dusk (-18°)
+    pub astronomical: RiseSet,
+}
+
+/// Equatorial coordinates from suncalc's low precision formulas
 #[derive(Debug)]
 pub struct Coords {
-    pub ra: f64,
-    pub dec: f64,
+    pub ra: Radians,
+    pub dec: Radians,
     pub dist: f64,
 }
 
@@ -28,22 +263,47 @@ pub struct CartesianCoordinates {
 }
 #[derive(Serialize,Deserialize,Debug, Clone)]
 pub struct HzCoordinates {
-    pub az: f64,
-    pub alt: f64,
+    /// Azimuth measured from north through east, within [0, 360)
+    pub az: Degrees,
+    pub alt: Degrees,
 }
 #[derive(Serialize,Deserialize,Debug, Clone)]
 pub struct EclCoordinates {
-    pub lat: f64,
-    pub lng: f64,
+    pub lat: Degrees,
+    /// Within [0, 360)
+    pub lng: Degrees,
     pub dist: f64,
 }
 #[derive(Serialize,Deserialize,Debug, Clone)]
 pub struct EqCoordinates {
-    pub ra: f64,
-    pub dec: f64,
+    /// Within [0, 360)
+    pub ra: Degrees,
+    pub dec: Degrees,
+    pub dist: f64,
+}
+
+/// Galactic longitude within [0, 360), latitude and distance
+#[derive(Serialize,Deserialize,Debug, Clone)]
+pub struct GalCoordinates {
+    pub l: Degrees,
+    pub b: Degrees,
+    pub dist: f64,
+}
+/// Supergalactic longitude within [0, 360), latitude and distance
+#[derive(Serialize,Deserialize,Debug, Clone)]
+pub struct SupergalCoordinates {
+    pub sgl: Degrees,
+    pub sgb: Degrees,
     pub dist: f64,
 }
 
+impl EqCoordinates {
+    /// Right ascension in hours, within [0, 24)
+    pub fn ra_hours(&self) -> HourAngle {
+        self.ra.to_hours().normalized()
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum CelestialBodyType {
     Planet,
@@ -56,24 +316,54 @@ pub enum CelestialBodyType {
 pub struct CelestialPosition {
     pub geo_cart: CartesianCoordinates,
     pub object_type: CelestialBodyType,
+    /// Instant of observation, UTC
     pub julian_day: f64,
+    /// Geocentric position corrected for light travel time, in the same frame as
+    /// `geo_cart`, when the body's motion was available to compute it
+    pub light_time_cart: Option<CartesianCoordinates>,
 }
 
 impl CelestialPosition {
-    pub fn from_helio_cart(
-        julian_day: f64,
+    /// Builds a position from heliocentric coordinates (AU, ecliptic of date) at the
+    /// instant `julian_day`
+    pub fn from_helio_cart<J: Into<JulianDate>>(
+        julian_day: J,
         helio_cart_coords: CartesianCoordinates,
         object_type: CelestialBodyType,
     ) -> Self {
-        let geo_coords = util::geo_frm_helio(julian_day, helio_cart_coords);
+        let date = julian_day.into();
+        let geo_coords = util::geo_frm_helio(date.to_tdb().jd, helio_cart_coords);
         return CelestialPosition {
             geo_cart: geo_coords,
             object_type: object_type,
-            julian_day: julian_day,
+            julian_day: date.to_utc().jd,
+            light_time_cart: None,
         };
     }
-    pub fn from_geo_eq(
-        julian_day: f64,
+    /// Builds a position from a function returning heliocentric coordinates (AU, ecliptic
+    /// of date) for any Julian day (TDB), so that light travel time can be corrected for
+    pub fn from_helio_fn<J, F>(julian_day: J, helio_cart: F, object_type: CelestialBodyType) -> Self
+    where
+        J: Into<JulianDate>,
+        F: Fn(f64) -> CartesianCoordinates,
+    {
+        let date = julian_day.into();
+        let tdb = date.to_tdb().jd;
+        let geo_coords = util::geo_frm_helio(tdb, helio_cart(tdb));
+        let mut light_time_coords = geo_coords.clone();
+        for _ in 0..3 {
+            let light_time = util::norm(&light_time_coords) / util::SPEED_OF_LIGHT;
+            light_time_coords = util::geo_frm_helio(tdb, helio_cart(tdb - light_time));
+        }
+        CelestialPosition {
+            geo_cart: geo_coords,
+            object_type,
+            julian_day: date.to_utc().jd,
+            light_time_cart: Some(light_time_coords),
+        }
+    }
+    pub fn from_geo_eq<J: Into<JulianDate>>(
+        julian_day: J,
         eq_coords: EqCoordinates,
         object_type: CelestialBodyType,
     ) -> Self {
@@ -81,54 +371,266 @@ impl CelestialPosition {
         return CelestialPosition {
             geo_cart: geo_coords,
             object_type: object_type,
-            julian_day: julian_day,
+            julian_day: julian_day.into().to_utc().jd,
+            light_time_cart: None,
         };
     }
+    /// `julian_day` as TDB, for the ephemerides
+    fn tdb(&self) -> f64 {
+        JulianDate::utc(self.julian_day).to_tdb().jd
+    }
     pub fn get_hz_coords(&self, location: Location) -> HzCoordinates {
-        let eq_coords = self.get_eq_coords();
-        let lw = util::RAD * -location.lon;
-        let phi = util::RAD * location.lat;
-        let d = util::julian_epoch_offset(self.julian_day);
-        let hour_angle = util::siderealTime(d, lw) - eq_coords.ra.to_radians();
-        let mut alt = util::altitude(hour_angle, phi, eq_coords.dec.to_radians());
-        alt = alt + util::astroRefraction(alt); // altitude correction for refraction
-        let az = util::azimuth(hour_angle, phi, eq_coords.dec.to_radians());
+        refract(self.get_true_hz_coords(location), location)
+    }
+    /// Horizontal coordinates without the correction for atmospheric refraction, from the
+    /// mean equator and equinox of date for every kind of body
+    pub fn get_true_hz_coords(&self, location: Location) -> HzCoordinates {
+        self.get_true_hz_coords_at(location, Equinox::MeanOfDate)
+    }
+    /// Horizontal coordinates, refraction included, computed from the equatorial
+    /// coordinates referred to `equinox`
+    pub fn get_hz_coords_at(&self, location: Location, equinox: Equinox) -> HzCoordinates {
+        refract(self.get_true_hz_coords_at(location, equinox), location)
+    }
+    /// Horizontal coordinates without refraction, computed from the equatorial coordinates
+    /// referred to `equinox`. Hour angles are counted on the equator of date, so `J2000`
+    /// coordinates are precessed to the mean equinox of date first and give the same
+    /// result as `MeanOfDate`.
+    pub fn get_true_hz_coords_at(&self, location: Location, equinox: Equinox) -> HzCoordinates {
+        let equinox = match equinox {
+            Equinox::J2000 => Equinox::MeanOfDate,
+            of_date => of_date,
+        };
+        self.hz_frm_eq(self.get_eq_coords_at(equinox), location, equinox)
+    }
+    /// Horizontal coordinates from equatorial ones of date, using apparent sidereal time
+    /// for true equatorial coordinates and mean sidereal time for mean ones
+    fn hz_frm_eq(&self, eq_coords: EqCoordinates, location: Location, equinox: Equinox) -> HzCoordinates {
+        let phi = Degrees(location.lat).to_radians().0;
+        let lst = match equinox {
+            Equinox::TrueOfDate => sidereal::local_sidereal_time(self.julian_day, location),
+            Equinox::MeanOfDate => sidereal::local_mean_sidereal_time(self.julian_day, location),
+            Equinox::J2000 => panic!("hour angles need equatorial coordinates of date"),
+        };
+        let hour_angle = HourAngle::from(lst - eq_coords.ra).to_radians().0;
+        let dec = eq_coords.dec.to_radians().0;
+        let alt = util::altitude(hour_angle, phi, dec);
+        let az = util::azimuth(hour_angle, phi, dec);
         return HzCoordinates {
-            az: az.to_degrees() + 180.0,
-            alt: alt.to_degrees(),
+            // util::azimuth counts from the south
+            az: (Radians(az).to_degrees() + Degrees(180.0)).normalized(),
+            alt: Radians(alt).to_degrees(),
         };
     }
-    pub fn get_ecl_coords(&self) -> EclCoordinates {
-        let mut coords = util::ecl_frm_cart(self.geo_cart.x, self.geo_cart.y, self.geo_cart.z);
-        coords.lat = coords.lat.to_degrees();
-        coords.lng = coords.lng.to_degrees();
-        return coords;
+    /// Geocentric equatorial cartesian coordinates referred to the mean equator and
+    /// equinox of J2000
+    pub fn get_j2000_eq_cart(&self) -> CartesianCoordinates {
+        self.j2000_frm_native(&self.geo_cart)
     }
-    pub fn get_eq_coords(&self) -> EqCoordinates {
-        let coords = match self.object_type {
-            CelestialBodyType::Star => util::eq_frm_cart(self.geo_cart.clone()),
+    fn j2000_frm_native(&self, cart: &CartesianCoordinates) -> CartesianCoordinates {
+        let to_j2000 = precession::transpose(&precession::precession_matrix(self.julian_day));
+        match self.object_type {
+            CelestialBodyType::Star => cart.clone(),
+            // moon_coords is referred to the equinox of date
+            CelestialBodyType::Moon => precession::apply(&to_j2000, cart),
+            // VSOP87C is referred to the ecliptic and equinox of date
             CelestialBodyType::Planet |
             CelestialBodyType::Sun => {
-                let ecl_coords = self.get_ecl_coords();
-                let oblq = astro::ecliptic::mn_oblq_IAU(self.julian_day);
-                let (_, geoeq_dec) = eq_frm_ecl!(
-                    ecl_coords.lng.to_radians(),
-                    ecl_coords.lat.to_radians(),
-                    oblq
-                );
-                let ra_sun = 360.0 +
-                    util::right_ascension(ecl_coords.lng.to_radians(), ecl_coords.lat.to_radians())
-                        .to_degrees();
-                return EqCoordinates {
-                    ra: ra_sun,
-                    dec: geoeq_dec.to_degrees(),
-                    dist: ecl_coords.dist,
-                };
+                let oblq = precession::mean_obliquity(self.julian_day);
+                let eq_cart = precession::apply(&precession::ecliptic_matrix(oblq), cart);
+                precession::apply(&to_j2000, &eq_cart)
             }
-            _ => unreachable!(),
+        }
+    }
+    /// Astrometric place: J2000 equatorial coordinates corrected for light travel time
+    pub fn get_astrometric_eq_coords(&self) -> EqCoordinates {
+        util::eq_frm_cart(self.astrometric_cart())
+    }
+    fn astrometric_cart(&self) -> CartesianCoordinates {
+        match self.light_time_cart {
+            Some(ref cart) => self.j2000_frm_native(cart),
+            None => self.get_j2000_eq_cart(),
+        }
+    }
+    /// Angular separation from another position, between astrometric places.
+    /// Accurate down to the smallest angles, unlike the haversine or cosine formulas.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use astral::Body;
+    /// let julian = 2458070.85; // 2017 November 13, Venus passes Jupiter
+    /// let venus = Body::Venus.position(julian);
+    /// let jupiter = Body::Jupiter.position(julian);
+    /// assert!((venus.separation(&jupiter).0 - 0.26).abs() < 0.01);
+    /// assert_eq!(venus.separation(&venus).0, 0.0);
+    /// // Jupiter is just west of due south of Venus
+    /// let angle = venus.position_angle(&jupiter).0;
+    /// assert!(angle > 180.0 && angle < 225.0);
+    /// ```
+    pub fn separation(&self, other: &CelestialPosition) -> Degrees {
+        Radians(util::angle_between(&self.astrometric_cart(), &other.astrometric_cart())).to_degrees()
+    }
+    /// Position angle of another position seen from this one, measured from north through
+    /// east within [0, 360), between astrometric places
+    pub fn position_angle(&self, other: &CelestialPosition) -> Degrees {
+        Radians(util::position_angle(&self.astrometric_cart(), &other.astrometric_cart())).to_degrees()
+    }
+    /// Apparent place: the astrometric place corrected for annual aberration and referred
+    /// to the true equator and equinox of date. The Moon travels with the Earth, so it gets
+    /// no annual aberration.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use astral::Body;
+    /// let jupiter = Body::Jupiter.position(2458061.2743171295);
+    /// let geometric = jupiter.get_eq_coords_at(astral::precession::Equinox::TrueOfDate);
+    /// let apparent = jupiter.get_apparent_eq_coords();
+    /// // light time and aberration move Jupiter by tens of arcseconds
+    /// assert!((geometric.ra - apparent.ra).abs().0 > 0.005);
+    /// ```
+    pub fn get_apparent_eq_coords(&self) -> EqCoordinates {
+        util::eq_frm_cart(self.apparent_cart())
+    }
+    fn apparent_cart(&self) -> CartesianCoordinates {
+        let astrometric = self.astrometric_cart();
+        let matrix = precession::equinox_matrix(self.julian_day, Equinox::TrueOfDate);
+        if let CelestialBodyType::Moon = self.object_type {
+            return precession::apply(&matrix, &astrometric);
+        }
+        let dist = util::norm(&astrometric);
+        let oblq = precession::mean_obliquity(self.julian_day);
+        let to_j2000 = precession::multiply(
+            &precession::transpose(&precession::precession_matrix(self.julian_day)),
+            &precession::ecliptic_matrix(oblq),
+        );
+        let velocity = precession::apply(&to_j2000, &util::earth_velocity(self.tdb()));
+        let u = CartesianCoordinates {
+            x: astrometric.x / dist + velocity.x / util::SPEED_OF_LIGHT,
+            y: astrometric.y / dist + velocity.y / util::SPEED_OF_LIGHT,
+            z: astrometric.z / dist + velocity.z / util::SPEED_OF_LIGHT,
+        };
+        let scale = dist / util::norm(&u);
+        let aberrated = CartesianCoordinates {
+            x: u.x * scale,
+            y: u.y * scale,
+            z: u.z * scale,
+        };
+        precession::apply(&matrix, &aberrated)
+    }
+    /// Topocentric place: the apparent place seen from `location` rather than from the
+    /// centre of the Earth
+    pub fn get_topocentric_eq_coords(&self, location: Location) -> EqCoordinates {
+        let apparent = self.apparent_cart();
+        let observer = util::observer_eq_cart(self.julian_day, location);
+        // star distances are in parsecs, everything else in AU
+        let unit = match self.object_type {
+            CelestialBodyType::Star => util::AU_IN_PARSECS,
+            _ => 1.0,
         };
-        return coords;
+        util::eq_frm_cart(CartesianCoordinates {
+            x: apparent.x - observer.x * unit,
+            y: apparent.y - observer.y * unit,
+            z: apparent.z - observer.z * unit,
+        })
+    }
+    /// Diurnal parallax in right ascension and declination: the topocentric place minus
+    /// the geocentric apparent place
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use astral::Body;
+    /// use astral::coords::Location;
+    /// let location = Location::new(38.44043, -122.71405).unwrap().with_elevation(50.0);
+    /// let (_, dec_parallax) = Body::Moon.position(2458061.2743171295).get_diurnal_parallax(location);
+    /// // seen from the northern hemisphere the Moon is shifted south by most of a degree
+    /// assert!(dec_parallax.0 < -0.5 && dec_parallax.0 > -1.0);
+    /// ```
+    pub fn get_diurnal_parallax(&self, location: Location) -> (Degrees, Degrees) {
+        let apparent = self.get_apparent_eq_coords();
+        let topocentric = self.get_topocentric_eq_coords(location);
+        ((topocentric.ra - apparent.ra).signed(), topocentric.dec - apparent.dec)
+    }
+    /// Horizontal coordinates of the topocentric place, refraction included
+    pub fn get_topocentric_hz_coords(&self, location: Location) -> HzCoordinates {
+        let topocentric = self.get_topocentric_eq_coords(location);
+        refract(self.hz_frm_eq(topocentric, location, Equinox::TrueOfDate), location)
+    }
+    /// Equatorial coordinates referred to the given equinox
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use astral::star;
+    /// use astral::precession::Equinox;
+    /// let polaris = star::get_celestial_position(2458061.2743171295, "Polaris").unwrap();
+    /// let j2000 = polaris.get_eq_coords_at(Equinox::J2000);
+    /// let of_date = polaris.get_eq_coords_at(Equinox::TrueOfDate);
+    /// assert!((j2000.ra.0 - 37.95).abs() < 0.01);
+    /// assert!((of_date.ra.0 - 43.5).abs() < 0.1);
+    /// ```
+    pub fn get_eq_coords_at(&self, equinox: Equinox) -> EqCoordinates {
+        let matrix = precession::equinox_matrix(self.julian_day, equinox);
+        util::eq_frm_cart(precession::apply(&matrix, &self.get_j2000_eq_cart()))
+    }
+    /// Geometric cartesian coordinates referred to any frame
+    pub fn get_cart_in(&self, frame: Frame) -> CartesianCoordinates {
+        frames::transform(&self.get_j2000_eq_cart(), Frame::ICRS, frame, self.julian_day)
+    }
+    /// Geometric spherical coordinates in any frame, picked by the type asked for and its
+    /// frame parameters
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use astral::Body;
+    /// use astral::coords::{EclCoordinates, HzCoordinates, Location};
+    /// use astral::precession::Equinox;
+    /// let location = Location::new(38.44043, -122.71405).unwrap();
+    /// let jupiter = Body::Jupiter.position(2458061.2743171295);
+    /// let hz: HzCoordinates = jupiter.get_coords_in(location);
+    /// let expected = jupiter.get_true_hz_coords_at(location, Equinox::TrueOfDate);
+    /// assert!((hz.az - expected.az).abs().0 < 1e-9 && (hz.alt - expected.alt).abs().0 < 1e-9);
+    /// let ecliptic: EclCoordinates = jupiter.get_coords_in(Equinox::J2000);
+    /// assert!(ecliptic.lat.abs().0 < 1.4); // Jupiter's orbit is inclined 1.3°
+    /// ```
+    pub fn get_coords_in<T: Spherical>(&self, params: T::Params) -> T {
+        T::from_cart(&self.get_cart_in(T::frame(params)))
+    }
+    /// Galactic coordinates of the geometric position
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use astral::star;
+    /// let deneb = star::get_celestial_position(2451545.0, "Deneb").unwrap();
+    /// let galactic = deneb.get_gal_coords();
+    /// // Deneb lies in the plane of the Milky Way, in Cygnus
+    /// assert!((galactic.l.0 - 84.3).abs() < 0.1 && (galactic.b.0 - 2.0).abs() < 0.1);
+    /// ```
+    pub fn get_gal_coords(&self) -> GalCoordinates {
+        self.get_coords_in(())
+    }
+    /// Ecliptic coordinates referred to the mean ecliptic and equinox of date
+    pub fn get_ecl_coords(&self) -> EclCoordinates {
+        self.get_coords_in(Equinox::MeanOfDate)
+    }
+    /// Equatorial coordinates in the body's native frame: J2000 for catalogue
+    /// stars, mean equator and equinox of date for the Sun, Moon and planets. Use
+    /// `get_eq_coords_at` to pick the frame explicitly.
+    pub fn get_eq_coords(&self) -> EqCoordinates {
+        match self.object_type {
+            CelestialBodyType::Star => self.get_eq_coords_at(Equinox::J2000),
+            _ => self.get_eq_coords_at(Equinox::MeanOfDate),
+        }
     }
 }
 
-
+/// Altitude correction for refraction under the location's atmosphere
+fn refract(mut coords: HzCoordinates, location: Location) -> HzCoordinates {
+    let alt = coords.alt.to_radians().0;
+    coords.alt = Radians(alt + location.refraction(alt)).to_degrees();
+    coords
+}
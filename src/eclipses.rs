@@ -0,0 +1,335 @@
+//! Solar and lunar eclipse predictions after Meeus, *Astronomical Algorithms*, chapter 54,
+//! with local circumstances of solar eclipses found from the topocentric Sun and Moon.
+use super::*;
+use coords::*;
+use std::f64::consts::PI;
+use time::JulianDate;
+
+const SYNODIC_MONTH: f64 = 29.530588861;
+/// Moon's radius in Earth equatorial radii
+const MOON_RADIUS: f64 = 0.272481;
+/// Sun's semi-diameter at 1 AU, degrees
+const SUN_SEMIDIAMETER: f64 = 959.63 / 3600.0;
+
+#[derive(Serialize,Deserialize,Debug, Clone, Copy, PartialEq)]
+pub enum EclipseType {
+    Total,
+    Annular,
+    /// Annular along part of the track and total along the rest
+    Hybrid,
+    Partial,
+    Penumbral,
+}
+
+#[derive(Serialize,Deserialize,Debug, Clone, Copy, PartialEq)]
+pub struct SolarEclipse {
+    pub eclipse_type: EclipseType,
+    /// Julian day (UTC) of greatest eclipse
+    pub greatest_eclipse: f64,
+    /// Fraction of the Sun's diameter covered at greatest eclipse; for total, annular and
+    /// hybrid eclipses the ratio of the Moon's apparent diameter to the Sun's
+    pub magnitude: f64,
+    /// Least distance from the shadow axis to the centre of the Earth, in Earth radii
+    pub gamma: f64,
+    /// Whether the shadow axis meets the Earth. A total or annular eclipse that is not
+    /// central only grazes a polar region with the edge of the umbra or antumbra, and
+    /// has no central line.
+    pub central: bool,
+}
+
+#[derive(Serialize,Deserialize,Debug, Clone, Copy, PartialEq)]
+pub struct LunarEclipse {
+    pub eclipse_type: EclipseType,
+    /// Julian day (UTC) of greatest eclipse
+    pub greatest_eclipse: f64,
+    /// Fraction of the Moon's diameter inside the umbra (negative for penumbral eclipses)
+    pub magnitude: f64,
+    /// Fraction of the Moon's diameter inside the penumbra
+    pub penumbral_magnitude: f64,
+    /// Least distance from the Moon's centre to the shadow axis, in Earth radii
+    pub gamma: f64,
+    /// Half durations in minutes of the penumbral, partial and total phases
+    pub penumbral_semiduration: f64,
+    pub partial_semiduration: Option<f64>,
+    pub total_semiduration: Option<f64>,
+}
+
+/// Circumstances of a solar eclipse for one observer, as Julian days
+#[derive(Serialize,Deserialize,Debug, Clone, Copy, PartialEq)]
+pub struct LocalSolarEclipse {
+    pub first_contact: f64,
+    /// Start of totality or annularity, if the observer is inside the central path
+    pub second_contact: Option<f64>,
+    pub maximum: f64,
+    pub third_contact: Option<f64>,
+    pub fourth_contact: f64,
+    /// Fraction of the Sun's diameter covered at maximum
+    pub magnitude: f64,
+    /// Fraction of the Sun's disc area covered at maximum
+    pub obscuration: f64,
+    /// Sun's altitude at maximum; the eclipse is not seen when it is negative
+    pub sun_altitude: Degrees,
+}
+
+/// Quantities of Meeus chapter 54 for the new (phase 0) or full (phase 0.5) moon `k`,
+/// times in TT
+struct Syzygy {
+    julian: f64,
+    gamma: f64,
+    u: f64,
+    m1: f64,
+}
+
+fn syzygy(k: f64) -> Option<Syzygy> {
+    let t = k / 1236.85;
+    let rad = PI / 180.0;
+    let mut julian = 2451550.09766 + SYNODIC_MONTH * k + t * t * (0.00015437 + t * (-0.000000150 + t * 0.00000000073));
+    let m = rad * (2.5534 + 29.10535670 * k + t * t * (-0.0000014 - t * 0.00000011));
+    let m1 = rad * (201.5643 + 385.81693528 * k + t * t * (0.0107582 + t * (0.00001238 - t * 0.000000058)));
+    let f = rad * (160.7108 + 390.67050284 * k + t * t * (-0.0016118 + t * (-0.00000227 + t * 0.000000011)));
+    let omega = rad * (124.7746 - 1.56375588 * k + t * t * (0.0020672 + t * 0.00000215));
+    let e = 1.0 - t * (0.002516 + t * 0.0000074);
+
+    if f.sin().abs() > 0.36 {
+        return None;
+    }
+
+    let f1 = f - rad * 0.02665 * omega.sin();
+    let a1 = rad * (299.77 + 0.107408 * k - 0.009173 * t * t);
+    julian += if k.fract() == 0.0 {
+        -0.4075 * m1.sin() + 0.1721 * e * m.sin()
+    } else {
+        -0.4065 * m1.sin() + 0.1727 * e * m.sin()
+    };
+    julian += 0.0161 * (2.0 * m1).sin() - 0.0097 * (2.0 * f1).sin() +
+        0.0073 * e * (m1 - m).sin() - 0.0050 * e * (m1 + m).sin() -
+        0.0023 * (m1 - 2.0 * f1).sin() + 0.0021 * e * (2.0 * m).sin() +
+        0.0012 * (m1 + 2.0 * f1).sin() + 0.0006 * e * (2.0 * m1 + m).sin() -
+        0.0004 * (3.0 * m1).sin() - 0.0003 * e * (m + 2.0 * f1).sin() +
+        0.0003 * a1.sin() - 0.0002 * e * (m - 2.0 * f1).sin() -
+        0.0002 * e * (2.0 * m1 - m).sin() - 0.0002 * omega.sin();
+
+    let p = 0.2070 * e * m.sin() + 0.0024 * e * (2.0 * m).sin() - 0.0392 * m1.sin() +
+        0.0116 * (2.0 * m1).sin() - 0.0073 * e * (m1 + m).sin() +
+        0.0067 * e * (m1 - m).sin() + 0.0118 * (2.0 * f1).sin();
+    let q = 5.2207 - 0.0048 * e * m.cos() + 0.0020 * e * (2.0 * m).cos() - 0.3299 * m1.cos() -
+        0.0060 * e * (m1 + m).cos() + 0.0041 * e * (m1 - m).cos();
+    let w = f1.cos().abs();
+    let gamma = (p * f1.cos() + q * f1.sin()) * (1.0 - 0.0048 * w);
+    let u = 0.0059 + 0.0046 * e * m.cos() - 0.0182 * m1.cos() + 0.0004 * (2.0 * m1).cos() -
+        0.0005 * (m + m1).cos();
+
+    Some(Syzygy {
+        julian,
+        gamma,
+        u,
+        m1,
+    })
+}
+
+/// Returns the first solar eclipse after `julian`
+///
+/// # Example
+///
+/// ```
+/// use astral::eclipses::{self, EclipseType};
+/// let eclipse = eclipses::next_solar(2457754.5); // 2017 January 1
+/// assert_eq!(eclipse.eclipse_type, EclipseType::Annular); // 2017 February 26
+/// let eclipse = eclipses::next_solar(eclipse.greatest_eclipse + 1.0);
+/// assert_eq!(eclipse.eclipse_type, EclipseType::Total); // 2017 August 21
+/// assert!((eclipse.gamma - 0.4367).abs() < 0.001);
+/// assert!(eclipse.central);
+/// // 2014 April 29: the antumbra only touched Antarctica
+/// let eclipse = eclipses::next_solar(2456748.5);
+/// assert_eq!(eclipse.eclipse_type, EclipseType::Annular);
+/// assert!(!eclipse.central);
+/// ```
+pub fn next_solar(julian: f64) -> SolarEclipse {
+    let julian = JulianDate::utc(julian).to_tt().jd;
+    let mut k = ((julian - 2451550.09766) / SYNODIC_MONTH).floor();
+    loop {
+        if let Some(s) = syzygy(k) {
+            if s.julian >= julian {
+                if let Some(eclipse) = solar_eclipse(&s) {
+                    return eclipse;
+                }
+            }
+        }
+        k += 1.0;
+    }
+}
+
+fn solar_eclipse(s: &Syzygy) -> Option<SolarEclipse> {
+    let gamma = s.gamma.abs();
+    if gamma > 1.5433 + s.u {
+        return None;
+    }
+    let (eclipse_type, magnitude) = if gamma < 0.9972 + s.u.abs() {
+        let eclipse_type = if s.u < 0.0 {
+            EclipseType::Total
+        } else if s.u > 0.0047 || s.u >= 0.00464 * (1.0 - gamma * gamma).max(0.0).sqrt() {
+            EclipseType::Annular
+        } else {
+            EclipseType::Hybrid
+        };
+        (eclipse_type, central_magnitude(s))
+    } else {
+        (EclipseType::Partial, (1.5433 + s.u - gamma) / (0.5461 + 2.0 * s.u))
+    };
+    Some(SolarEclipse {
+        eclipse_type,
+        greatest_eclipse: JulianDate::tt(s.julian).to_utc().jd,
+        magnitude,
+        gamma: s.gamma,
+        central: gamma < 0.9972,
+    })
+}
+
+/// Ratio of the Moon's to the Sun's apparent diameter seen from the point of greatest
+/// eclipse, which lies `sqrt(1 - gamma²)` Earth radii closer to the Moon than the centre
+fn central_magnitude(s: &Syzygy) -> f64 {
+    let moon = Body::Moon.position(JulianDate::tt(s.julian)).get_apparent_eq_coords();
+    let sun = Body::Sun.position(JulianDate::tt(s.julian)).get_apparent_eq_coords();
+    let earth_radius = util::EARTH_RADIUS_IN_KM / util::AU_IN_KM;
+    let height = earth_radius * (1.0 - s.gamma * s.gamma).max(0.0).sqrt();
+    let moon_semidiameter = (MOON_RADIUS * earth_radius / (moon.dist - height)).asin().to_degrees();
+    let sun_semidiameter = SUN_SEMIDIAMETER / (sun.dist - height);
+    moon_semidiameter / sun_semidiameter
+}
+
+/// Returns the first lunar eclipse, penumbral ones included, after `julian`
+///
+/// # Example
+///
+/// ```
+/// use astral::eclipses::{self, EclipseType};
+/// let eclipse = eclipses::next_lunar(2458119.5); // 2018 January 1
+/// assert_eq!(eclipse.eclipse_type, EclipseType::Total); // 2018 January 31
+/// assert!((eclipse.magnitude - 1.3155).abs() < 0.01);
+/// ```
+pub fn next_lunar(julian: f64) -> LunarEclipse {
+    let julian = JulianDate::utc(julian).to_tt().jd;
+    let mut k = ((julian - 2451550.09766) / SYNODIC_MONTH).floor() + 0.5;
+    loop {
+        if let Some(s) = syzygy(k) {
+            if s.julian >= julian {
+                if let Some(eclipse) = lunar_eclipse(&s) {
+                    return eclipse;
+                }
+            }
+        }
+        k += 1.0;
+    }
+}
+
+fn lunar_eclipse(s: &Syzygy) -> Option<LunarEclipse> {
+    let gamma = s.gamma.abs();
+    let penumbral_magnitude = (1.5573 + s.u - gamma) / 0.5450;
+    let magnitude = (1.0128 - s.u - gamma) / 0.5450;
+    if penumbral_magnitude <= 0.0 {
+        return None;
+    }
+    let eclipse_type = if magnitude >= 1.0 {
+        EclipseType::Total
+    } else if magnitude > 0.0 {
+        EclipseType::Partial
+    } else {
+        EclipseType::Penumbral
+    };
+    let n = 0.5458 + 0.0400 * s.m1.cos();
+    let semiduration = |radius: f64| -> Option<f64> {
+        if radius > gamma {
+            Some(60.0 / n * (radius * radius - gamma * gamma).sqrt())
+        } else {
+            None
+        }
+    };
+    Some(LunarEclipse {
+        eclipse_type,
+        greatest_eclipse: JulianDate::tt(s.julian).to_utc().jd,
+        magnitude,
+        penumbral_magnitude,
+        gamma: s.gamma,
+        penumbral_semiduration: semiduration(1.5573 + s.u).unwrap_or(0.0),
+        partial_semiduration: semiduration(1.0128 - s.u),
+        total_semiduration: semiduration(0.4678 - s.u),
+    })
+}
+
+/// Contacts, magnitude and obscuration of a solar eclipse seen from `location`, or `None`
+/// if the Moon does not cover any of the Sun there
+///
+/// # Example
+///
+/// ```
+/// use astral::eclipses;
+/// use astral::coords::Location;
+/// let eclipse = eclipses::next_solar(2457966.5); // 2017 August 21
+/// let madras = Location::new(44.63, -121.13).unwrap(); // Madras, Oregon
+/// let local = eclipses::local_solar(&eclipse, madras).unwrap();
+/// assert!(local.second_contact.is_some());
+/// assert!((local.obscuration - 1.0).abs() < 1e-6);
+/// let santa_rosa = Location::new(38.44043, -122.71405).unwrap();
+/// let local = eclipses::local_solar(&eclipse, santa_rosa).unwrap();
+/// assert!(local.second_contact.is_none());
+/// assert!(local.magnitude > 0.7 && local.magnitude < 0.85);
+/// ```
+pub fn local_solar(eclipse: &SolarEclipse, location: Location) -> Option<LocalSolarEclipse> {
+    let discs = |julian: f64| -> (f64, f64, f64) {
+        let sun = Body::Sun.position(julian).get_topocentric_eq_coords(location);
+        let moon = Body::Moon.position(julian).get_topocentric_eq_coords(location);
+        let earth_radius = util::EARTH_RADIUS_IN_KM / util::AU_IN_KM;
+        let separation = util::angular_separation(&sun, &moon).0;
+        let moon_semidiameter = (MOON_RADIUS * earth_radius / moon.dist).asin().to_degrees();
+        (separation, SUN_SEMIDIAMETER / sun.dist, moon_semidiameter)
+    };
+    let outer = |julian: f64| {
+        let (separation, sun, moon) = discs(julian);
+        separation - (sun + moon)
+    };
+    let inner = |julian: f64| {
+        let (separation, sun, moon) = discs(julian);
+        separation - (sun - moon).abs()
+    };
+
+    let (start, end) = (eclipse.greatest_eclipse - 0.2, eclipse.greatest_eclipse + 0.2);
+    let maximum = rise_set::golden_section_max(&|julian| -outer(julian), start, end);
+    if outer(maximum) >= 0.0 {
+        return None;
+    }
+    let first_contact = rise_set::bisect(&outer, start, maximum);
+    let fourth_contact = rise_set::bisect(&outer, maximum, end);
+    let (second_contact, third_contact) = if inner(maximum) < 0.0 {
+        (Some(rise_set::bisect(&inner, first_contact, maximum)), Some(rise_set::bisect(&inner, maximum, fourth_contact)))
+    } else {
+        (None, None)
+    };
+
+    let (separation, sun, moon) = discs(maximum);
+    let sun_altitude = Body::Sun.position(maximum).get_topocentric_hz_coords(location).alt;
+    Some(LocalSolarEclipse {
+        first_contact,
+        second_contact,
+        maximum,
+        third_contact,
+        fourth_contact,
+        magnitude: (sun + moon - separation) / (2.0 * sun),
+        obscuration: obscuration(separation, sun, moon),
+        sun_altitude,
+    })
+}
+
+/// Fraction of the Sun's disc area covered by the Moon's, all angles in degrees
+fn obscuration(separation: f64, sun: f64, moon: f64) -> f64 {
+    if separation >= sun + moon {
+        return 0.0;
+    }
+    if separation <= (sun - moon).abs() {
+        return if moon >= sun { 1.0 } else { (moon * moon) / (sun * sun) };
+    }
+    let (d, r1, r2) = (separation, sun, moon);
+    let a1 = ((d * d + r1 * r1 - r2 * r2) / (2.0 * d * r1)).acos();
+    let a2 = ((d * d + r2 * r2 - r1 * r1) / (2.0 * d * r2)).acos();
+    let lens = r1 * r1 * (a1 - a1.sin() * a1.cos()) + r2 * r2 * (a2 - a2.sin() * a2.cos());
+    lens / (PI * r1 * r1)
+}
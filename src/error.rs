@@ -0,0 +1,36 @@
+use std::error;
+use std::fmt;
+
+/// Errors returned by the fallible functions of this crate
+#[derive(Debug, Clone, PartialEq)]
+pub enum Error {
+    /// No planet, star or other body matches the given name
+    UnknownBody(String),
+    /// A row of a star catalogue could not be parsed. `row` counts data rows from 1,
+    /// not including the header.
+    CatalogParse { row: usize, message: String },
+    /// Latitude or longitude outside of [-90, 90] and [-180, 180] degrees
+    InvalidLocation { lat: f64, lon: f64 },
+    /// A catalogue file could not be read
+    Io(String),
+    /// The Julian day lies outside of the range a model is valid for
+    OutOfRangeEpoch(f64),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::UnknownBody(ref name) => write!(f, "unknown body: {}", name),
+            Error::CatalogParse { row, ref message } => {
+                write!(f, "could not parse catalogue row {}: {}", row, message)
+            }
+            Error::InvalidLocation { lat, lon } => {
+                write!(f, "invalid location: lat {}, lon {}", lat, lon)
+            }
+            Error::Io(ref message) => write!(f, "i/o error: {}", message),
+            Error::OutOfRangeEpoch(julian) => write!(f, "epoch out of range: JD {}", julian),
+        }
+    }
+}
+
+impl error::Error for Error {}
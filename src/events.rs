@@ -0,0 +1,270 @@
+//! Searches a date range for planetary phenomena: conjunctions with the Sun, oppositions,
+//! greatest elongations, stationary points and close approaches between bodies.
+//!
+//! Positions are apparent and geocentric, sampled once a day and refined to the second.
+//! Each body's positions are cached per instant, so the refinements share the daily
+//! samples and never compute the same position twice.
+use super::*;
+use body::PLANETS;
+use coords::*;
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+const SAMPLE_STEP: f64 = 1.0; // days
+
+#[derive(Serialize,Deserialize,Debug, Clone, Copy, PartialEq)]
+pub enum PlanetEventKind {
+    /// An outer planet in line with the Sun
+    Conjunction,
+    /// Mercury or Venus passing between the Earth and the Sun
+    InferiorConjunction,
+    /// Mercury or Venus passing behind the Sun
+    SuperiorConjunction,
+    Opposition,
+    GreatestEasternElongation,
+    GreatestWesternElongation,
+    /// Motion in longitude turns westward, starting the retrograde loop
+    StationaryRetrograde,
+    /// Motion in longitude turns eastward again
+    StationaryPrograde,
+    /// Least separation from another planet or the Moon
+    CloseApproach,
+}
+
+#[derive(Serialize,Deserialize,Debug, Clone, PartialEq)]
+pub struct PlanetEvent {
+    pub kind: PlanetEventKind,
+    pub body: Body,
+    /// The other body of a close approach
+    pub other: Option<Body>,
+    pub julian_day: f64,
+    /// Angular distance from the Sun, or from `other` for close approaches
+    pub separation: Degrees,
+}
+
+/// Returns every planetary event between two Julian days in chronological order:
+/// the phenomena of each planet and the close approaches, no wider than
+/// `max_separation`, of each pair of planets and of each planet with the Moon
+///
+/// # Example
+///
+/// ```
+/// use astral::{events, Body, Degrees};
+/// use astral::events::PlanetEventKind;
+/// let found = events::find_events(2458061.5, 2458071.5, Degrees(1.0)); // 2017 November 5 to 15
+/// let approach = found.iter().find(|e| e.kind == PlanetEventKind::CloseApproach).unwrap();
+/// assert_eq!((&approach.body, approach.other.as_ref()), (&Body::Venus, Some(&Body::Jupiter)));
+/// assert!(approach.separation < Degrees(0.3));
+/// ```
+pub fn find_events(start: f64, end: f64, max_separation: Degrees) -> Vec<PlanetEvent> {
+    let times = sample_times(start, end);
+    let sun = Positions::new(&Body::Sun, &times);
+    let moon = Positions::new(&Body::Moon, &times);
+    let planets: Vec<Positions> = PLANETS.iter().map(|body| Positions::new(body, &times)).collect();
+
+    let mut events = vec![];
+    for (i, planet) in planets.iter().enumerate() {
+        events.extend(sun_events(planet, &sun, &times));
+        for other in planets.iter().skip(i + 1) {
+            events.extend(approaches(planet, other, &times, max_separation));
+        }
+        events.extend(approaches(planet, &moon, &times, max_separation));
+    }
+    events.sort_by(|a, b| a.julian_day.total_cmp(&b.julian_day));
+    events
+}
+
+/// Returns the conjunctions, oppositions, greatest elongations and stationary points of a
+/// planet between two Julian days; other bodies have none
+///
+/// # Example
+///
+/// ```
+/// use astral::{events, Body};
+/// use astral::events::PlanetEventKind::*;
+/// let found = events::planet_events(&Body::Venus, 2457754.5, 2457935.5); // 2017 H1
+/// let kinds: Vec<_> = found.iter().map(|event| event.kind).collect();
+/// assert_eq!(kinds, vec![GreatestEasternElongation, StationaryRetrograde,
+///                        InferiorConjunction, StationaryPrograde, GreatestWesternElongation]);
+/// assert!((found[0].separation.0 - 47.1).abs() < 0.1); // January 12
+/// ```
+pub fn planet_events(body: &Body, start: f64, end: f64) -> Vec<PlanetEvent> {
+    if !body.is_planet() {
+        return vec![];
+    }
+    let times = sample_times(start, end);
+    sun_events(&Positions::new(body, &times), &Positions::new(&Body::Sun, &times), &times)
+}
+
+/// Returns the instants of least separation between two bodies that come within
+/// `max_separation` of each other between two Julian days
+///
+/// # Example
+///
+/// ```
+/// use astral::{events, Body, Degrees};
+/// let found = events::close_approaches(&Body::Venus, &Body::Jupiter, 2458061.5, 2458071.5, Degrees(1.0));
+/// assert_eq!(found.len(), 1);
+/// assert!((found[0].julian_day - 2458070.75).abs() < 0.5); // 2017 November 13
+/// ```
+pub fn close_approaches(
+    first: &Body,
+    second: &Body,
+    start: f64,
+    end: f64,
+    max_separation: Degrees,
+) -> Vec<PlanetEvent> {
+    let times = sample_times(start, end);
+    approaches(
+        &Positions::new(first, &times),
+        &Positions::new(second, &times),
+        &times,
+        max_separation,
+    )
+}
+
+/// Apparent geocentric equatorial coordinates of a body, computed at most once per instant
+struct Positions<'a> {
+    body: &'a Body,
+    cache: RefCell<HashMap<u64, EqCoordinates>>,
+}
+
+impl<'a> Positions<'a> {
+    /// Positions of `body`, computed up front at the sample `times`
+    fn new(body: &'a Body, times: &[f64]) -> Positions<'a> {
+        let positions = Positions {
+            body,
+            cache: RefCell::new(HashMap::new()),
+        };
+        for t in times {
+            positions.at(*t);
+        }
+        positions
+    }
+
+    fn at(&self, julian: f64) -> EqCoordinates {
+        return self.cache
+            .borrow_mut()
+            .entry(julian.to_bits())
+            .or_insert_with(|| self.body.position(julian).get_apparent_eq_coords())
+            .clone();
+    }
+}
+
+fn sun_events(planet: &Positions, sun: &Positions, times: &[f64]) -> Vec<PlanetEvent> {
+    let body = planet.body;
+    let inferior = *body == Body::Mercury || *body == Body::Venus;
+    let longitude = |julian: f64| ecliptic_longitude(&planet.at(julian), julian);
+    // planet's longitude minus the Sun's, within [-180, 180)
+    let offset = |julian: f64| wrap(longitude(julian) - ecliptic_longitude(&sun.at(julian), julian));
+    let opposition_offset = |julian: f64| wrap(offset(julian) + 180.0);
+    let elongation = |julian: f64| util::angular_separation(&planet.at(julian), &sun.at(julian)).0;
+    let event = |kind: PlanetEventKind, julian: f64| PlanetEvent {
+        kind,
+        body: body.clone(),
+        other: None,
+        julian_day: julian,
+        separation: Degrees(elongation(julian)),
+    };
+
+    let longitudes: Vec<f64> = times.iter().map(|t| longitude(*t)).collect();
+    let offsets: Vec<f64> = times.iter().map(|t| offset(*t)).collect();
+    let elongations: Vec<f64> = times.iter().map(|t| elongation(*t)).collect();
+
+    let mut events = vec![];
+    for i in 1..times.len() {
+        let (t0, t1) = (times[i - 1], times[i]);
+        let (f0, f1) = (offsets[i - 1], offsets[i]);
+
+        // a genuine crossing of zero, not the jump between +180 and -180
+        if (f0 < 0.0) != (f1 < 0.0) && (f1 - f0).abs() < 90.0 {
+            let julian = rise_set::bisect(&offset, t0, t1);
+            let kind = if !inferior {
+                PlanetEventKind::Conjunction
+            } else if planet.at(julian).dist < sun.at(julian).dist {
+                PlanetEventKind::InferiorConjunction
+            } else {
+                PlanetEventKind::SuperiorConjunction
+            };
+            events.push(event(kind, julian));
+        }
+        let (o0, o1) = (wrap(f0 + 180.0), wrap(f1 + 180.0));
+        if !inferior && (o0 < 0.0) != (o1 < 0.0) && (o1 - o0).abs() < 90.0 {
+            events.push(event(PlanetEventKind::Opposition, rise_set::bisect(&opposition_offset, t0, t1)));
+        }
+
+        if i + 1 < times.len() {
+            let t2 = times[i + 1];
+            if inferior && elongations[i] > elongations[i - 1] && elongations[i] >= elongations[i + 1] {
+                let julian = rise_set::golden_section_max(&elongation, t0, t2);
+                let kind = if offset(julian) > 0.0 {
+                    PlanetEventKind::GreatestEasternElongation
+                } else {
+                    PlanetEventKind::GreatestWesternElongation
+                };
+                events.push(event(kind, julian));
+            }
+
+            // daily motion, taken at the midpoints between samples
+            let (d0, d1) = (wrap(longitudes[i] - longitudes[i - 1]), wrap(longitudes[i + 1] - longitudes[i]));
+            if (d0 < 0.0) != (d1 < 0.0) {
+                let half = SAMPLE_STEP / 2.0;
+                let rate = |julian: f64| wrap(longitude(julian + half) - longitude(julian - half));
+                let julian = rise_set::bisect(&rate, t0 + half, t1 + half);
+                let kind = if d0 > 0.0 {
+                    PlanetEventKind::StationaryRetrograde
+                } else {
+                    PlanetEventKind::StationaryPrograde
+                };
+                events.push(event(kind, julian));
+            }
+        }
+    }
+    events.sort_by(|a, b| a.julian_day.total_cmp(&b.julian_day));
+    events
+}
+
+fn approaches(first: &Positions, second: &Positions, times: &[f64], max_separation: Degrees) -> Vec<PlanetEvent> {
+    let distance = |julian: f64| util::angular_separation(&first.at(julian), &second.at(julian)).0;
+    let separations: Vec<f64> = times.iter().map(|t| distance(*t)).collect();
+
+    let mut events = vec![];
+    for i in 1..separations.len().saturating_sub(1) {
+        if separations[i] < separations[i - 1] && separations[i] <= separations[i + 1] {
+            let julian = rise_set::golden_section_max(&|t| -distance(t), times[i - 1], times[i + 1]);
+            let least = distance(julian);
+            if least <= max_separation.0 {
+                events.push(PlanetEvent {
+                    kind: PlanetEventKind::CloseApproach,
+                    body: first.body.clone(),
+                    other: Some(second.body.clone()),
+                    julian_day: julian,
+                    separation: Degrees(least),
+                });
+            }
+        }
+    }
+    events
+}
+
+fn sample_times(start: f64, end: f64) -> Vec<f64> {
+    let mut times = vec![];
+    let mut t = start;
+    while t < end {
+        times.push(t);
+        t += SAMPLE_STEP;
+    }
+    times.push(end);
+    times
+}
+
+/// Apparent ecliptic longitude of date in degrees from apparent equatorial coordinates
+fn ecliptic_longitude(eq: &EqCoordinates, julian: f64) -> f64 {
+    let oblq = precession::true_obliquity(julian);
+    astro::coords::ecl_long_frm_eq(eq.ra.to_radians().0, eq.dec.to_radians().0, oblq).to_degrees()
+}
+
+/// Wraps an angle in degrees into [-180, 180)
+fn wrap(angle: f64) -> f64 {
+    angle - 360.0 * ((angle + 180.0) / 360.0).floor()
+}
@@ -0,0 +1,225 @@
+//! Reference frames and the rotations between them. Every rotation goes through ICRS,
+//! taken as the mean equator and equinox of J2000 (the frame bias, 23 mas, is ignored).
+use super::*;
+use coords::*;
+use precession::{Equinox, Matrix};
+use units::{Degrees, Radians};
+
+/// North galactic pole and galactic longitude of the north celestial pole, ICRS degrees
+/// (Hipparcos, vol. 1, §1.5.3)
+const GALACTIC_POLE_RA: f64 = 192.85948;
+const GALACTIC_POLE_DEC: f64 = 27.12825;
+const GALACTIC_NODE_LONGITUDE: f64 = 122.93192 - 90.0;
+/// North supergalactic pole in galactic degrees (de Vaucouleurs, 1976); the origin of
+/// supergalactic longitude is its node on the galactic equator, at l = 137.37°
+const SUPERGALACTIC_POLE_L: f64 = 47.37;
+const SUPERGALACTIC_POLE_B: f64 = 6.32;
+
+#[derive(Serialize,Deserialize,Debug, Clone, Copy, PartialEq)]
+pub enum Frame {
+    /// Equator and equinox given by `Equinox`; `Equinox::J2000` stands for ICRS
+    Equatorial(Equinox),
+    /// Ecliptic and equinox given by `Equinox`, with the mean obliquity for `J2000` and
+    /// `MeanOfDate` and the true obliquity for `TrueOfDate`
+    Ecliptic(Equinox),
+    Galactic,
+    Supergalactic,
+    /// Horizon of an observer, from the true equator of date and apparent sidereal time.
+    /// Cartesian axes point south, east and to the zenith; refraction is not included.
+    Horizontal(Location),
+}
+
+impl Frame {
+    pub const ICRS: Frame = Frame::Equatorial(Equinox::J2000);
+
+    /// Rotation from ICRS to this frame at the UTC Julian day `julian`
+    pub fn matrix(&self, julian: f64) -> Matrix {
+        match *self {
+            Frame::Equatorial(equinox) => precession::equinox_matrix(julian, equinox),
+            Frame::Ecliptic(equinox) => {
+                let obliquity = match equinox {
+                    Equinox::J2000 => precession::mean_obliquity(util::J2000),
+                    Equinox::MeanOfDate => precession::mean_obliquity(julian),
+                    Equinox::TrueOfDate => precession::true_obliquity(julian),
+                };
+                let to_ecliptic = precession::transpose(&precession::ecliptic_matrix(obliquity));
+                precession::multiply(&to_ecliptic, &precession::equinox_matrix(julian, equinox))
+            }
+            Frame::Galactic => galactic_matrix(),
+            Frame::Supergalactic => precession::multiply(&supergalactic_matrix(), &galactic_matrix()),
+            Frame::Horizontal(location) => {
+                let lst = sidereal::local_sidereal_time(julian, location).to_radians().0;
+                let colatitude = Degrees(90.0 - location.lat).to_radians().0;
+                let to_hour_angle = precession::multiply(
+                    &precession::rot_y(colatitude),
+                    &precession::rot_z(lst),
+                );
+                precession::multiply(&to_hour_angle, &precession::equinox_matrix(julian, Equinox::TrueOfDate))
+            }
+        }
+    }
+}
+
+/// Rotation from the frame `from` to the frame `to` at the UTC Julian day `julian`
+pub fn matrix(from: Frame, to: Frame, julian: f64) -> Matrix {
+    precession::multiply(&to.matrix(julian), &precession::transpose(&from.matrix(julian)))
+}
+
+/// Cartesian coordinates given in `from` referred to `to` instead
+///
+/// # Example
+///
+/// ```
+/// use astral::frames::{self, Frame};
+/// use astral::coords::CartesianCoordinates;
+/// // the north galactic pole
+/// let pole = CartesianCoordinates { x: 0.0, y: 0.0, z: 1.0 };
+/// let icrs = frames::transform(&pole, Frame::Galactic, Frame::ICRS, 2451545.0);
+/// let back = frames::transform(&icrs, Frame::ICRS, Frame::Galactic, 2451545.0);
+/// assert!((icrs.z - 27.12825f64.to_radians().sin()).abs() < 1e-12);
+/// assert!((back.z - 1.0).abs() < 1e-12);
+/// ```
+pub fn transform(cart: &CartesianCoordinates, from: Frame, to: Frame, julian: f64) -> CartesianCoordinates {
+    precession::apply(&matrix(from, to, julian), cart)
+}
+
+/// Rotation from ICRS to galactic coordinates: about the pole to the node of the galactic
+/// equator, over to the galactic pole, then along the galactic equator to l = 0
+fn galactic_matrix() -> Matrix {
+    pole_matrix(GALACTIC_POLE_RA, GALACTIC_POLE_DEC, GALACTIC_NODE_LONGITUDE)
+}
+
+/// Rotation from galactic to supergalactic coordinates
+fn supergalactic_matrix() -> Matrix {
+    pole_matrix(SUPERGALACTIC_POLE_L, SUPERGALACTIC_POLE_B, 0.0)
+}
+
+/// Rotation to the frame whose pole is at (`pole_lon`, `pole_lat`) and whose longitude
+/// is `node_lon` at its ascending node on the old equator, all in degrees
+fn pole_matrix(pole_lon: f64, pole_lat: f64, node_lon: f64) -> Matrix {
+    precession::multiply(
+        &precession::rot_z(-node_lon.to_radians()),
+        &precession::multiply(
+            &precession::rot_x((90.0 - pole_lat).to_radians()),
+            &precession::rot_z((pole_lon + 90.0).to_radians()),
+        ),
+    )
+}
+
+/// Spherical coordinates in one kind of frame, with `Params` picking the frame within its
+/// kind: the equinox for equatorial and ecliptic coordinates, the observer for horizontal
+/// ones, nothing for galactic and supergalactic ones
+pub trait Spherical: Sized {
+    type Params: Copy;
+    fn frame(params: Self::Params) -> Frame;
+    fn to_cart(&self) -> CartesianCoordinates;
+    fn from_cart(cart: &CartesianCoordinates) -> Self;
+
+    /// These coordinates, given in the frame `from`, converted to another frame at the UTC
+    /// Julian day `julian`
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use astral::frames::Spherical;
+    /// use astral::coords::{EqCoordinates, GalCoordinates};
+    /// use astral::precession::Equinox;
+    /// use astral::Degrees;
+    /// // Sagittarius A*
+    /// let sgr_a = EqCoordinates { ra: Degrees(266.41683), dec: Degrees(-29.00781), dist: 1.0 };
+    /// let galactic: GalCoordinates = sgr_a.to_frame(Equinox::J2000, (), 2451545.0);
+    /// assert!(galactic.l.signed().abs().0 < 0.1 && galactic.b.abs().0 < 0.1);
+    /// ```
+    fn to_frame<T: Spherical>(&self, from: Self::Params, to: T::Params, julian: f64) -> T {
+        T::from_cart(&transform(&self.to_cart(), Self::frame(from), T::frame(to), julian))
+    }
+}
+
+/// Longitude within [0, 360), latitude and distance of cartesian coordinates
+fn spherical_frm_cart(cart: &CartesianCoordinates) -> (Degrees, Degrees, f64) {
+    let dist = util::norm(cart);
+    let lon = Radians(cart.y.atan2(cart.x)).to_degrees().normalized();
+    let lat = Radians((cart.z / dist).asin()).to_degrees();
+    (lon, lat, dist)
+}
+
+fn cart_frm_spherical(lon: Degrees, lat: Degrees, dist: f64) -> CartesianCoordinates {
+    CartesianCoordinates {
+        x: dist * lat.cos() * lon.cos(),
+        y: dist * lat.cos() * lon.sin(),
+        z: dist * lat.sin(),
+    }
+}
+
+impl Spherical for EqCoordinates {
+    type Params = Equinox;
+    fn frame(equinox: Equinox) -> Frame {
+        Frame::Equatorial(equinox)
+    }
+    fn to_cart(&self) -> CartesianCoordinates {
+        util::cart_frm_eq(self.clone())
+    }
+    fn from_cart(cart: &CartesianCoordinates) -> EqCoordinates {
+        util::eq_frm_cart(cart.clone())
+    }
+}
+
+impl Spherical for EclCoordinates {
+    type Params = Equinox;
+    fn frame(equinox: Equinox) -> Frame {
+        Frame::Ecliptic(equinox)
+    }
+    fn to_cart(&self) -> CartesianCoordinates {
+        cart_frm_spherical(self.lng, self.lat, self.dist)
+    }
+    fn from_cart(cart: &CartesianCoordinates) -> EclCoordinates {
+        util::ecl_frm_cart(cart.x, cart.y, cart.z)
+    }
+}
+
+impl Spherical for GalCoordinates {
+    type Params = ();
+    fn frame(_: ()) -> Frame {
+        Frame::Galactic
+    }
+    fn to_cart(&self) -> CartesianCoordinates {
+        cart_frm_spherical(self.l, self.b, self.dist)
+    }
+    fn from_cart(cart: &CartesianCoordinates) -> GalCoordinates {
+        let (l, b, dist) = spherical_frm_cart(cart);
+        GalCoordinates { l, b, dist }
+    }
+}
+
+impl Spherical for SupergalCoordinates {
+    type Params = ();
+    fn frame(_: ()) -> Frame {
+        Frame::Supergalactic
+    }
+    fn to_cart(&self) -> CartesianCoordinates {
+        cart_frm_spherical(self.sgl, self.sgb, self.dist)
+    }
+    fn from_cart(cart: &CartesianCoordinates) -> SupergalCoordinates {
+        let (sgl, sgb, dist) = spherical_frm_cart(cart);
+        SupergalCoordinates { sgl, sgb, dist }
+    }
+}
+
+/// Horizontal coordinates carry no distance: they convert to and from unit vectors
+impl Spherical for HzCoordinates {
+    type Params = Location;
+    fn frame(location: Location) -> Frame {
+        Frame::Horizontal(location)
+    }
+    fn to_cart(&self) -> CartesianCoordinates {
+        // x points south, so the azimuth from the north is measured from -x
+        cart_frm_spherical(Degrees(180.0) - self.az, self.alt, 1.0)
+    }
+    fn from_cart(cart: &CartesianCoordinates) -> HzCoordinates {
+        let (lon, alt, _) = spherical_frm_cart(cart);
+        HzCoordinates {
+            az: (Degrees(180.0) - lon).normalized(),
+            alt,
+        }
+    }
+}
@@ -10,9 +10,11 @@ extern crate serde_derive;
 extern crate astro;
 extern crate vsop87;
 extern crate chrono;
+#[macro_use]
+extern crate lazy_static;
 
 use chrono::prelude::*;
-use chrono::{DateTime, NaiveDateTime};
+use chrono::DateTime;
 
 pub use astro::angle;
 pub mod ascii_art;
@@ -20,29 +22,80 @@ pub mod moon;
 pub mod sun;
 pub mod planet;
 pub mod star;
+pub mod rise_set;
+pub mod body;
+pub use body::Body;
+pub mod error;
+pub use error::Error;
 #[macro_use]
 pub mod util;
+pub mod units;
+pub use units::{Degrees, HourAngle, Radians};
 pub mod coords;
+pub mod precession;
+pub mod frames;
+pub mod locale;
+pub mod eclipses;
+pub mod events;
+pub mod time;
+pub mod sidereal;
+pub use time::JulianDate;
 
 #[cfg(test)]
 mod tests {
     use planet;
-    // use sun;
+    use sun;
+    use moon;
     use star;
+    use rise_set;
     use coords::*;
+    use Error;
+    use Body;
+    use precession::Equinox;
+    use time::JulianDate;
+    use units::Degrees;
     // use chrono::prelude::*;
     const JULIAN_DAY: f64 = 2458061.2743171295;
     const LOCATION: Location = Location {
         lat: 38.44043,
         lon: -122.71405,
+        elevation: 0.0,
+        pressure: 1010.0,
+        temperature: 10.0,
     };
 
     #[test]
     fn get_celestial_position_star_test() {
         let celestial = star::get_celestial_position(JULIAN_DAY, "Polaris").unwrap();
         let tolerence = 2.0;
-        assert_approx_eq!(37.954522, celestial.get_eq_coords().ra, tolerence);
-        assert_approx_eq!(89.264108, celestial.get_eq_coords().dec, tolerence);
+        assert_approx_eq!(37.954522, celestial.get_eq_coords().ra.0, tolerence);
+        assert_approx_eq!(89.264108, celestial.get_eq_coords().dec.0, tolerence);
+    }
+
+    #[test]
+    fn star_hz_coords_of_date_test() {
+        // 2026-10-18, where precession since J2000 moves Sirius by about a fifth of a degree
+        let julian = 2461331.5;
+        let sirius = star::StarCatalog::embedded().get("Sirius").unwrap();
+        let position = sirius.get_celestial_position(julian);
+        let hz = sirius.get_hz_coords(julian, LOCATION);
+        let of_date = position.get_hz_coords_at(LOCATION, Equinox::MeanOfDate);
+        assert_eq!((hz.az, hz.alt), (of_date.az, of_date.alt));
+        // J2000 coordinates are precessed to date before the hour angle is taken, so they
+        // agree with the apparent place to within nutation
+        let j2000 = position.get_hz_coords_at(LOCATION, Equinox::J2000);
+        let true_of_date = position.get_hz_coords_at(LOCATION, Equinox::TrueOfDate);
+        assert_eq!((j2000.az, j2000.alt), (of_date.az, of_date.alt));
+        assert!((j2000.az - true_of_date.az).abs().0 < 0.01 && (j2000.alt - true_of_date.alt).abs().0 < 0.01);
+    }
+
+    #[test]
+    fn star_diurnal_parallax_test() {
+        // Proxima Centauri's diurnal parallax is about 3e-5", far below a milliarcsecond
+        let proxima = star::get_celestial_position(JULIAN_DAY, "Proxima Centauri").unwrap();
+        let (ra_parallax, dec_parallax) = proxima.get_diurnal_parallax(LOCATION);
+        assert!(ra_parallax.abs().0 * 3600.0 < 1e-3);
+        assert!(dec_parallax.abs().0 * 3600.0 < 1e-3);
     }
 
     #[test]
@@ -51,11 +104,204 @@ mod tests {
         let tolerence = 0.004f64;
         let dec = -8.45970181351729;
         let ra = 204.04900114888179;
-        let az = 172.90148843919002;
-        let alt = 42.86299764225456;
-        assert_approx_eq!(dec, celestial.get_eq_coords().dec, tolerence);
-        assert_approx_eq!(ra, celestial.get_eq_coords().ra, tolerence);
-        assert_approx_eq!(az, celestial.get_hz_coords(LOCATION).az, tolerence);
-        assert_approx_eq!(alt, celestial.get_hz_coords(LOCATION).alt, tolerence);
+        let az = 173.51251791628957;
+        let alt = 42.90567324847266;
+        assert_approx_eq!(dec, celestial.get_eq_coords().dec.0, tolerence);
+        assert_approx_eq!(ra, celestial.get_eq_coords().ra.0, tolerence);
+        assert_approx_eq!(az, celestial.get_hz_coords(LOCATION).az.0, tolerence);
+        assert_approx_eq!(alt, celestial.get_hz_coords(LOCATION).alt.0, tolerence);
+    }
+
+    #[test]
+    fn get_times_sun_test() {
+        let times = sun::get_times(JULIAN_DAY, LOCATION);
+        let tolerence = 0.002; // just under 3 minutes
+        assert_approx_eq!(2458061.3303, times.solar_noon, tolerence);
+        assert_approx_eq!(2458061.1119, times.sun.rise().unwrap(), tolerence);
+        assert_approx_eq!(2458061.5488, times.sun.set().unwrap(), tolerence);
+        assert!(times.civil.rise().unwrap() < times.sun.rise().unwrap());
+        assert!(times.astronomical.set().unwrap() > times.nautical.set().unwrap());
+    }
+
+    #[test]
+    fn get_rise_set_moon_test() {
+        // local midnight, 2017-11-04 PDT
+        let times = moon::get_rise_set(2458061.7916667, LOCATION);
+        let tolerence = 2.0 / 1440.0;
+        assert_approx_eq!(2458062.5852, times.rise.unwrap(), tolerence);
+        assert_approx_eq!(2458062.1255, times.set.unwrap(), tolerence);
+        assert_approx_eq!(2458061.8389, times.transit.unwrap(), tolerence);
+        assert!(!times.always_up && !times.always_down);
+        // the Moon culminated just before this window opened, so no transit is reported
+        let times = moon::get_rise_set(2458061.8389 + 0.01, LOCATION);
+        assert!(times.transit.is_none());
+    }
+
+    #[test]
+    fn culmination_in_first_step_test() {
+        let events = rise_set::find_events(&Body::Jupiter, LOCATION, JULIAN_DAY, JULIAN_DAY + 1.0, None);
+        let culmination = events.iter().find(|e| e.kind == HorizonEventKind::Culmination).unwrap();
+        // open the window three minutes before the culmination, well inside the first step
+        let start = culmination.julian_day - 3.0 / 1440.0;
+        let events = rise_set::find_events(&Body::Jupiter, LOCATION, start, start + 0.5, None);
+        let found = events.iter().find(|e| e.kind == HorizonEventKind::Culmination).unwrap();
+        assert_approx_eq!(culmination.julian_day, found.julian_day, 1.0 / 1440.0);
+    }
+
+    #[test]
+    fn unknown_body_error_test() {
+        let err = planet::get_celestial_position(JULIAN_DAY, "Vulcan").unwrap_err();
+        assert_eq!(err, Error::UnknownBody("Vulcan".to_string()));
+        let err = planet::get_celestial_position(JULIAN_DAY, "Polaris").unwrap_err();
+        assert_eq!(err, Error::UnknownBody("Polaris".to_string()));
+        assert!(Location::new(91.0, 0.0).is_err());
+    }
+
+    #[test]
+    fn apparent_place_planet_test() {
+        // Meeus, Astronomical Algorithms, example 33.a
+        let apparent = Body::Venus.position(JulianDate::tt(2448976.5)).get_apparent_eq_coords();
+        let tolerence = 0.0001;
+        assert_approx_eq!(316.172725, apparent.ra.0, tolerence);
+        assert_approx_eq!(-18.888011, apparent.dec.0, tolerence);
+    }
+
+    #[test]
+    fn moon_position_test() {
+        // Meeus, Astronomical Algorithms, example 47.a
+        let moon = Body::Moon.position(JulianDate::tt(2448724.5));
+        let apparent = moon.get_eq_coords_at(Equinox::TrueOfDate);
+        let tolerence = 0.001;
+        assert_approx_eq!(134.688470, apparent.ra.0, tolerence);
+        assert_approx_eq!(13.768368, apparent.dec.0, tolerence);
+        assert_approx_eq!(368409.7, apparent.dist * ::util::AU_IN_KM, 1.0);
+    }
+
+    #[test]
+    fn location_atmosphere_test() {
+        let summit = LOCATION.with_elevation(1500.0);
+        let sea_level = sun::get_times(JULIAN_DAY, LOCATION);
+        let from_summit = sun::get_times(JULIAN_DAY, summit);
+        // 1.1° of horizon dip moves sunrise earlier by several minutes
+        assert!(sea_level.sun.rise().unwrap() - from_summit.sun.rise().unwrap() > 0.004);
+        // Saemundsson's formula at 1010 mbar and 10°C, in arcminutes
+        let arcmin = |refraction: f64| refraction.to_degrees() * 60.0;
+        assert_approx_eq!(28.98, arcmin(LOCATION.refraction(0.0)), 0.01);
+        assert_approx_eq!(5.41, arcmin(LOCATION.refraction(10.0f64.to_radians())), 0.01);
+        // denser air bends more, by (1030 / 1010) * (283 / 253)
+        let cold = LOCATION.with_pressure(1030.0).with_temperature(-20.0);
+        assert_approx_eq!(33.06, arcmin(cold.refraction(0.0)), 0.01);
+        // and lifts the Moon over the horizon sooner
+        let moonrise = |location: Location| moon::get_rise_set(JULIAN_DAY, location).rise.unwrap();
+        assert!(moonrise(LOCATION) - moonrise(cold) > 2.0 / 86400.0);
+        // below -1° the formula is held at its -1° value
+        assert_eq!(LOCATION.refraction((-5.0f64).to_radians()), LOCATION.refraction((-1.0f64).to_radians()));
+    }
+
+    #[test]
+    fn separation_small_angle_test() {
+        let position = |ra: f64, dec: f64| {
+            CelestialPosition::from_geo_eq(
+                JULIAN_DAY,
+                EqCoordinates { ra: Degrees(ra), dec: Degrees(dec), dist: 1.0 },
+                CelestialBodyType::Star,
+            )
+        };
+        let arcsecond = 1.0 / 3600.0;
+        let a = position(80.0, 45.0);
+        let north = position(80.0, 45.0 + arcsecond);
+        let east = position(80.0 + arcsecond / 45f64.to_radians().cos(), 45.0);
+        assert!((a.separation(&north).0 / arcsecond - 1.0).abs() < 1e-6);
+        assert!((a.separation(&east).0 / arcsecond - 1.0).abs() < 1e-4);
+        assert!(a.position_angle(&north).signed().abs().0 < 1e-4);
+        assert!((a.position_angle(&east).0 - 90.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn frame_round_trip_test() {
+        use frames::{self, Frame, Spherical};
+        use util;
+        let frames = [
+            Frame::ICRS,
+            Frame::Equatorial(Equinox::MeanOfDate),
+            Frame::Equatorial(Equinox::TrueOfDate),
+            Frame::Ecliptic(Equinox::J2000),
+            Frame::Ecliptic(Equinox::TrueOfDate),
+            Frame::Galactic,
+            Frame::Supergalactic,
+            Frame::Horizontal(LOCATION),
+        ];
+        let cart = CartesianCoordinates { x: 0.3, y: -0.5, z: 0.8 };
+        for &from in frames.iter() {
+            for &to in frames.iter() {
+                let there = frames::transform(&cart, from, to, JULIAN_DAY);
+                let back = frames::transform(&there, to, from, JULIAN_DAY);
+                assert_approx_eq!(util::norm(&cart), util::norm(&there), 1e-12);
+                assert_approx_eq!(cart.x, back.x, 1e-12);
+                assert_approx_eq!(cart.y, back.y, 1e-12);
+                assert_approx_eq!(cart.z, back.z, 1e-12);
+            }
+        }
+        // ICRS to galactic, Hipparcos vol. 1, §1.5.3
+        let galactic = frames::matrix(Frame::ICRS, Frame::Galactic, JULIAN_DAY);
+        assert_approx_eq!(-0.0548755604, galactic[0][0], 1e-9);
+        assert_approx_eq!(0.7469822445, galactic[1][2], 1e-9);
+        assert_approx_eq!(-0.1980763734, galactic[2][1], 1e-9);
+        // the supergalactic origin and pole, given in galactic coordinates
+        let origin = GalCoordinates { l: Degrees(137.37), b: Degrees(0.0), dist: 1.0 };
+        let origin: SupergalCoordinates = origin.to_frame((), (), JULIAN_DAY);
+        assert!(origin.sgl.signed().abs().0 < 1e-9 && origin.sgb.abs().0 < 1e-9);
+        let pole = GalCoordinates { l: Degrees(47.37), b: Degrees(6.32), dist: 1.0 };
+        let pole: SupergalCoordinates = pole.to_frame((), (), JULIAN_DAY);
+        assert_approx_eq!(90.0, pole.sgb.0, 1e-9);
+        // Meeus, example 13.a: Pollux on the ecliptic of J2000
+        let pollux = EqCoordinates { ra: Degrees(116.328942), dec: Degrees(28.026183), dist: 1.0 };
+        let ecliptic: EclCoordinates = pollux.to_frame(Equinox::J2000, Equinox::J2000, JULIAN_DAY);
+        assert_approx_eq!(113.215630, ecliptic.lng.0, 1e-4);
+        assert_approx_eq!(6.684170, ecliptic.lat.0, 1e-4);
+        // back from the horizon
+        let hz = HzCoordinates { az: Degrees(120.0), alt: Degrees(35.0) };
+        let eq: EqCoordinates = hz.to_frame(LOCATION, Equinox::TrueOfDate, JULIAN_DAY);
+        let again: HzCoordinates = eq.to_frame(Equinox::TrueOfDate, LOCATION, JULIAN_DAY);
+        assert_approx_eq!(120.0, again.az.0, 1e-9);
+        assert_approx_eq!(35.0, again.alt.0, 1e-9);
+    }
+
+    #[test]
+    fn time_scale_test() {
+        use time::{self, TimeScale};
+        // either side of the leap second at the end of 2016
+        for &jd in [2457754.4999, 2457754.5001, 2433282.5, 1000000.5].iter() {
+            let utc = JulianDate::utc(jd);
+            for &scale in [TimeScale::Tt, TimeScale::Tdb, TimeScale::Ut1].iter() {
+                assert_approx_eq!(jd, utc.to_scale(scale).to_utc().jd, 1e-8);
+            }
+        }
+        assert_approx_eq!(68.184, (JulianDate::utc(2457754.4999).to_tt().jd - 2457754.4999) * 86400.0, 1e-3);
+        assert_approx_eq!(69.184, (JulianDate::utc(2457754.5001).to_tt().jd - 2457754.5001) * 86400.0, 1e-3);
+        assert!(time::tai_minus_utc(2441000.5).is_none());
+        // Meeus, example 10.a: ΔT of about 33 s in 1957
+        assert_approx_eq!(31.8, time::delta_t(2436116.31), 1.0);
+    }
+
+    #[test]
+    fn julian_round_trip_test() {
+        use chrono::prelude::*;
+        use util;
+        let tz = FixedOffset::east_opt(5 * 3600 + 1800).unwrap();
+        for &(seconds, nanos) in [(0, 1), (-1, 999_999_999), (1509795301, 123_456_789), (-12219292800, 500)].iter() {
+            let date = tz.timestamp_opt(seconds, nanos).unwrap();
+            let (day, fraction) = util::to_julian_parts(date);
+            assert_eq!(date, util::from_julian_parts(day, fraction).unwrap());
+            let julian = util::to_julian(date);
+            let back = util::from_julian_tz(julian, &tz).unwrap();
+            assert!((back - date).num_microseconds().unwrap().abs() < 100);
+        }
+        // the Unix epoch and the Gregorian reform
+        assert_eq!(util::to_julian(Utc.timestamp_opt(0, 0).unwrap()), 2440587.5);
+        assert_eq!(util::to_julian(Utc.timestamp_opt(-12219292800, 0).unwrap()), 2299160.5);
+        // out of chrono's range instead of a panic
+        assert!(util::from_julian(f64::NAN).is_none());
+        assert!(util::from_julian_parts(2440587.5, 1e12).is_none());
     }
 }
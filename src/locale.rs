@@ -0,0 +1,61 @@
+//! Translated names for moon phases. Implement `MoonPhaseLocale` to plug in another
+//! language, or build a `PhaseNameTable`.
+use coords::MoonPhase;
+
+pub trait MoonPhaseLocale {
+    fn phase_name(&self, phase: MoonPhase) -> &str;
+}
+
+/// Phase names in `MoonPhase` declaration order, from new moon to waning crescent
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PhaseNameTable(pub [&'static str; 8]);
+
+impl MoonPhaseLocale for PhaseNameTable {
+    fn phase_name(&self, phase: MoonPhase) -> &str {
+        self.0[phase as usize]
+    }
+}
+
+pub const ENGLISH: PhaseNameTable = PhaseNameTable([
+    "new",
+    "waxing crescent",
+    "first quarter",
+    "waxing gibbous",
+    "full",
+    "waning gibbous",
+    "last quarter",
+    "waning crescent",
+]);
+
+pub const SPANISH: PhaseNameTable = PhaseNameTable([
+    "luna nueva",
+    "luna creciente",
+    "cuarto creciente",
+    "gibosa creciente",
+    "luna llena",
+    "gibosa menguante",
+    "cuarto menguante",
+    "luna menguante",
+]);
+
+pub const FRENCH: PhaseNameTable = PhaseNameTable([
+    "nouvelle lune",
+    "premier croissant",
+    "premier quartier",
+    "gibbeuse croissante",
+    "pleine lune",
+    "gibbeuse décroissante",
+    "dernier quartier",
+    "dernier croissant",
+]);
+
+pub const GERMAN: PhaseNameTable = PhaseNameTable([
+    "Neumond",
+    "zunehmende Sichel",
+    "erstes Viertel",
+    "zunehmender Mond",
+    "Vollmond",
+    "abnehmender Mond",
+    "letztes Viertel",
+    "abnehmende Sichel",
+]);
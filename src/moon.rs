@@ -3,53 +3,205 @@ use super::*;
 use util::*;
 use coords::*;
 use sun::sun_coords;
+use time::JulianDate;
+use sidereal;
+use units::{Degrees, Radians};
 use std::f64::consts::PI;
 
 pub fn get_lunar_info(julian: f64, location: Location) -> LunarInfo {
-    let moon_position = getMoonPosition(julian, location.lat, location.lon);
-    let phase_name = getMoonPhaseName(getMoonPhase(julian));
-    let phase_image = getMoonPhaseImage(phase_name);
+    let moon_position = get_topocentric_position(julian, location);
+    let phase = MoonPhase::from_phase(getMoonPhase(julian));
+    let phase_image = getMoonPhaseImage(phase);
     return LunarInfo {
         altitude: moon_position.alt,
         azimuth: moon_position.az,
         percent_illuminated: getMoonIllumination(julian) * 100.0,
-        phase_name: phase_name.to_string(),
+        phase,
         phase_image: phase_image.to_string(),
     };
 }
 
-pub fn getMoonPhaseImage<'a>(phase_name: &str) -> &'a str {
-    let image;
-    image = match phase_name {
-        "new" => MOON_IMAGE_NEW,
-        "waxing crescent" => MOON_IMAGE_WAXING_C,
-        "first quarter" => MOON_IMAGE_FIRST_Q,
-        "waxing gibbous" => MOON_IMAGE_WAXING_G,
-        "full" => MOON_IMAGE_FULL,
-        "waning gibbous" => MOON_IMAGE_WANING_G,
-        "last quarter" => MOON_IMAGE_LAST_Q,
-        "waning crescent" => MOON_IMAGE_WANING_C,
-        _ => "no image found",
+/// Returns the geocentric CelestialPosition of the Moon
+pub fn get_celestial_position<J: Into<JulianDate>>(julian: J) -> CelestialPosition {
+    let date = julian.into();
+    let c = moon_coords(julian_epoch_offset(date.to_tt().jd));
+    let eq_coords = EqCoordinates {
+        ra: c.ra.to_degrees(),
+        dec: c.dec.to_degrees(),
+        dist: c.dist / AU_IN_KM,
     };
-    return &image;
+    CelestialPosition {
+        geo_cart: cart_frm_eq(eq_coords),
+        object_type: CelestialBodyType::Moon,
+        julian_day: date.to_utc().jd,
+        light_time_cart: None,
+    }
+}
+
+/// Returns the Moon's altitude and azimuth as seen by an observer on the Earth's surface,
+/// corrected for parallax (up to a degree) and refraction. `getMoonPosition` is geocentric.
+pub fn get_topocentric_position(julian: f64, location: Location) -> HzCoordinates {
+    get_celestial_position(julian).get_topocentric_hz_coords(location)
+}
+
+pub fn getMoonPhaseImage(phase: MoonPhase) -> &'static str {
+    match phase {
+        MoonPhase::New => MOON_IMAGE_NEW,
+        MoonPhase::WaxingCrescent => MOON_IMAGE_WAXING_C,
+        MoonPhase::FirstQuarter => MOON_IMAGE_FIRST_Q,
+        MoonPhase::WaxingGibbous => MOON_IMAGE_WAXING_G,
+        MoonPhase::Full => MOON_IMAGE_FULL,
+        MoonPhase::WaningGibbous => MOON_IMAGE_WANING_G,
+        MoonPhase::LastQuarter => MOON_IMAGE_LAST_Q,
+        MoonPhase::WaningCrescent => MOON_IMAGE_WANING_C,
+    }
 }
 
 pub fn getMoonPosition(date: f64, lat: f64, lng: f64) -> HzCoordinates {
-    let lw = RAD * -lng;
+    let mut coords = geometric_position(date, lat, lng);
+    let h = coords.alt.to_radians().0;
+    coords.alt = Radians(h + astroRefraction(h)).to_degrees(); // altitude correction for refraction
+    coords
+}
+
+/// Geocentric altitude and azimuth of the Moon, as in `getMoonPosition` but without refraction
+fn geometric_position(date: f64, lat: f64, lng: f64) -> HzCoordinates {
     let phi = RAD * lat;
-    let d = julian_epoch_offset(date);
-    let c = moon_coords(d);
-    let hour_angle = siderealTime(d, lw) - c.ra;
-    let mut h = altitude(hour_angle, phi, c.dec);
-    h = h + astroRefraction(h); // altitude correction for refraction
+    let date = JulianDate::utc(date);
+    let c = moon_coords(julian_epoch_offset(date.to_tt().jd));
+    let lst = (sidereal::gmst(date.to_ut1().jd) + Degrees(lng)).to_radians();
+    let hour_angle = (lst - c.ra).0;
     return HzCoordinates {
-        az: azimuth(hour_angle, phi, c.dec).to_degrees()+180.0,
-        alt: h.to_degrees(),
+        az: (Radians(azimuth(hour_angle, phi, c.dec.0)).to_degrees() + Degrees(180.0)).normalized(),
+        alt: Radians(altitude(hour_angle, phi, c.dec.0)).to_degrees(),
     };
 }
 
+/// Altitude (degrees) of the Moon's centre, geocentric and refracted, at the instant its
+/// upper limb touches the horizon for a topocentric observer.
+fn moon_horizon_altitude(julian: f64) -> f64 {
+    let dist = moon_coords(julian_epoch_offset(JulianDate::utc(julian).to_tt().jd)).dist;
+    let parallax = astro::lunar::eq_hz_parllx(dist);
+    let semidiameter = astro::lunar::semidiameter(dist);
+    (parallax - semidiameter).to_degrees()
+}
+
+/// Returns moonrise, moonset and upper transit in the 24 hours following `julian`
+///
+/// The altitude is sampled every hour and crossings are refined by quadratic
+/// interpolation, as in suncalc's `getMoonTimes`.
+///
+/// # Arguments
+///
+/// * `julian` - Julian day the search starts from (usually local midnight)
+/// * `location` - Observer location
+///
+/// # Example
+///
+/// ```
+/// use astral::moon;
+/// use astral::coords::Location;
+/// let location = Location::new(38.44043, -122.71405).unwrap();
+/// let times = moon::get_rise_set(2458061.0, location);
+/// assert!(times.rise.is_some() || times.set.is_some());
+/// ```
+pub fn get_rise_set(julian: f64, location: Location) -> MoonTimes {
+    let step = 1.0 / 24.0;
+    // refracted under the location's own atmosphere rather than the standard one
+    let height = |jd: f64| -> f64 {
+        let alt = geometric_position(jd, location.lat, location.lon).alt.to_radians().0;
+        Radians(alt + location.refraction(alt)).to_degrees().0 - moon_horizon_altitude(jd) +
+            location.horizon_dip()
+    };
+
+    let mut rise = None;
+    let mut set = None;
+    let mut transit = None;
+    let mut h0 = height(julian);
+    let mut hprev = height(julian - step);
+    let mut highest = h0;
+    let mut lowest = h0;
+
+    let mut i = 1;
+    while i <= 24 {
+        let t = julian + i as f64 * step;
+        let h1 = height(t);
+        let h2 = height(t + step);
+        highest = highest.max(h1).max(h2);
+        lowest = lowest.min(h1).min(h2);
+
+        // fit a parabola through the three samples, x in [-1, 1] around t
+        let a = (h0 + h2) / 2.0 - h1;
+        let b = (h2 - h0) / 2.0;
+        let xe = -b / (2.0 * a);
+        let ye = (a * xe + b) * xe + h1;
+        let discriminant = b * b - 4.0 * a * h1;
+
+        // upper transit: samples stop climbing either inside this window or on its left edge,
+        // where the peak can fall just before `julian`
+        if transit.is_none() {
+            let peak = if h1 > h0 && h2 <= h1 {
+                Some(t + xe.clamp(-1.0, 1.0) * step)
+            } else if h1 <= h0 && h0 > hprev {
+                let (a, b) = ((hprev + h1) / 2.0 - h0, (h1 - hprev) / 2.0);
+                Some(t - step + (-b / (2.0 * a)).clamp(-1.0, 1.0) * step)
+            } else {
+                None
+            };
+            transit = peak.filter(|&peak| peak >= julian && peak <= julian + 1.0);
+        }
+
+        if discriminant >= 0.0 {
+            let dx = discriminant.sqrt() / (a.abs() * 2.0);
+            let mut x1 = xe - dx;
+            let x2 = xe + dx;
+            let mut roots = 0;
+            if x1.abs() <= 1.0 {
+                roots += 1;
+            }
+            if x2.abs() <= 1.0 {
+                roots += 1;
+            }
+            if x1 < -1.0 {
+                x1 = x2;
+            }
+            if roots == 1 {
+                if h0 < 0.0 {
+                    rise = rise.or(Some(t + x1 * step));
+                } else {
+                    set = set.or(Some(t + x1 * step));
+                }
+            } else if roots == 2 {
+                let (first, second) = (t + x1 * step, t + x2 * step);
+                if ye < 0.0 {
+                    rise = rise.or(Some(second));
+                    set = set.or(Some(first));
+                } else {
+                    rise = rise.or(Some(first));
+                    set = set.or(Some(second));
+                }
+            }
+        }
+
+        if rise.is_some() && set.is_some() && transit.is_some() {
+            break;
+        }
+        hprev = h1;
+        h0 = h2;
+        i += 2;
+    }
+
+    MoonTimes {
+        rise,
+        set,
+        transit,
+        always_up: rise.is_none() && set.is_none() && lowest > 0.0,
+        always_down: rise.is_none() && set.is_none() && highest < 0.0,
+    }
+}
+
 pub fn getMoonIllumination(julian: f64) -> f64 {
-    let d = julian_epoch_offset(julian);
+    let d = julian_epoch_offset(JulianDate::utc(julian).to_tt().jd);
     let s = sun::sun_coords(d);
     let m = moon_coords(d);
     let phi = (s.dec.sin() * m.dec.sin() + s.dec.cos() * m.dec.cos() * (s.ra - m.ra).cos()).acos();
@@ -59,23 +211,76 @@ pub fn getMoonIllumination(julian: f64) -> f64 {
 }
 
 pub fn getMoonPhaseName<'a>(moon_phase: f64) -> &'a str {
-    // Floating point ranges not allowed
-    let phase = (moon_phase * 100.0) as i32;
-    let phase_name = match phase {
-        0...03 => "new",
-        02...20 => "waxing crescent",
-        20...30 => "first quarter",
-        30...47 => "waxing gibbous",
-        47...53 => "full",
-        53...70 => "waning gibbous",
-        70...85 => "last quarter",
-        85...99 => "waning crescent",
-        _ => "anything",
+    MoonPhase::from_phase(moon_phase).name()
+}
+
+/// Apparent geocentric ecliptic longitude of the Moon minus that of the Sun, within
+/// [0, 360)
+pub fn elongation(julian: f64) -> Degrees {
+    let (moon_ecl, _) = astro::lunar::geocent_ecl_pos(JulianDate::utc(julian).to_tt().jd);
+    let sun_ecl = sun::get_celestial_position(julian).get_ecl_coords();
+    // annual aberration of the Sun
+    let sun_long = sun_ecl.lng - Degrees(20.4898 / 3600.0 / sun_ecl.dist);
+    (Radians(moon_ecl.long).to_degrees() - sun_long).normalized()
+}
+
+/// Returns the exact instants of new moon, first quarter, full moon and last quarter
+/// between two Julian days, in chronological order
+///
+/// # Example
+///
+/// ```
+/// use astral::moon;
+/// use astral::coords::MoonPhase;
+/// let events = moon::phase_events(2458058.5, 2458088.5); // November 2017
+/// assert_eq!(events.len(), 4);
+/// assert_eq!(events[0].phase, MoonPhase::Full);
+/// assert!((events[0].julian_day - 2458061.7247).abs() < 0.002); // Nov 4 05:23 UT
+/// ```
+pub fn phase_events(start: f64, end: f64) -> Vec<PhaseEvent> {
+    let phases = [
+        MoonPhase::New,
+        MoonPhase::FirstQuarter,
+        MoonPhase::Full,
+        MoonPhase::LastQuarter,
+    ];
+    // offset from the target elongation, wrapped into [-180, 180)
+    let offset = |julian: f64, target: Degrees| -> f64 {
+        (elongation(julian) - target).signed().0
     };
-    return phase_name;
+
+    let step = 1.0;
+    let mut events = vec![];
+    let mut t0 = start;
+    while t0 < end {
+        let t1 = (t0 + step).min(end);
+        for phase in phases.iter() {
+            let target = phase.elongation().unwrap();
+            let (f0, f1) = (offset(t0, target), offset(t1, target));
+            // the elongation only increases, so a crossing goes from below to above
+            if f0 < 0.0 && f1 >= 0.0 && f1 - f0 < 180.0 {
+                let (mut lo, mut hi) = (t0, t1);
+                while hi - lo > 1.0 / 86400.0 {
+                    let mid = (lo + hi) / 2.0;
+                    if offset(mid, target) < 0.0 {
+                        lo = mid;
+                    } else {
+                        hi = mid;
+                    }
+                }
+                events.push(PhaseEvent {
+                    phase: *phase,
+                    julian_day: (lo + hi) / 2.0,
+                });
+            }
+        }
+        t0 = t1;
+    }
+    events.sort_by(|a, b| a.julian_day.total_cmp(&b.julian_day));
+    events
 }
 pub fn getMoonPhase(julian: f64) -> f64 {
-    let d = julian_epoch_offset(julian);
+    let d = julian_epoch_offset(JulianDate::utc(julian).to_tt().jd);
     let s = sun_coords(d);
     let m = moon_coords(d);
 
@@ -91,20 +296,19 @@ pub fn getMoonPhase(julian: f64) -> f64 {
     return 0.5 + 0.5 * inc * some_phase_var / PI;
 }
 
+/// Geocentric equatorial coordinates of the Moon (radians, km), referred to the mean
+/// equator and equinox of date, for `d` days of TT since J2000
+///
+/// Uses the ELP-2000/82 series of Meeus, *Astronomical Algorithms*, chapter 47, which is
+/// good to about 10" in longitude and 4" in latitude.
 pub fn moon_coords(d: f64) -> Coords {
-    // geocentric ecliptic coordinates of the moon
-    let L = RAD * (218.316 + 13.176396 * d); // ecliptic longitude
-    let M = RAD * (134.963 + 13.064993 * d); // mean anomaly
-    let F = RAD * (93.272 + 13.229350 * d); // mean distance
-
-    let l = L + RAD * 6.289 * M.sin(); // longitude
-    let b = RAD * 5.128 * F.sin(); // latitude
-    // println!("long,lat(deg) {},{}", l.to_degrees(),b.to_degrees());
-    let dt = 385001.0 - 20905.0 * M.cos(); // distance to the moon in km
-
+    let julian = d + J2000;
+    let (ecl_point, dist) = astro::lunar::geocent_ecl_pos(julian);
+    let oblq = precession::mean_obliquity(julian);
+    let (ra, dec) = eq_frm_ecl!(ecl_point.long, ecl_point.lat, oblq);
     return Coords {
-        ra: right_ascension(l, b),
-        dec: declination(l, b),
-        dist: dt,
+        ra: Radians(ra),
+        dec: Radians(dec),
+        dist,
     };
 }
@@ -1,45 +1,260 @@
-use vsop87::vsop87c;
+use super::*;
 use coords::*;
+use body::Body;
+use error::Error;
+use time::JulianDate;
 
 /// Returns the CelestialPosition of a planet
 ///
 /// # Arguments
 ///
 /// * `julian` - Julian day
-/// * `planet_name` - Planet Name
+/// * `planet_name` - Planet Name. "Earth" is accepted too and gives the geocentre.
+///
+/// Returns `Error::OutOfRangeEpoch` outside of the span VSOP87 is accurate over, see
+/// `check_epoch`.
 ///
 /// # Example
 ///
 /// ```
-/// use astral::planet;
-/// let jd = 24000.0;
+/// use astral::{planet, Error};
+/// let jd = 2458061.5;
 /// assert_eq!(planet::get_celestial_position(jd,"Mars").unwrap().julian_day,jd);
 /// assert_eq!(planet::get_celestial_position(jd,"mars").unwrap().julian_day,jd);
+/// assert!(planet::get_celestial_position(jd,"earth").unwrap().geo_cart.x.abs() < 1e-12);
+/// let ancient = 24000.0; // 4648 BC
+/// assert_eq!(planet::get_celestial_position(ancient,"Mars").unwrap_err(), Error::OutOfRangeEpoch(ancient));
 /// ```
-pub fn get_celestial_position(julian: f64, planet_name: &str) -> Result<CelestialPosition, &'static str>  {
-
-    if let Some(helio_coords) = match planet_name.to_lowercase().as_str() {
-        "venus" => Some(vsop87c::venus(julian)),
-        "earth" => Some(vsop87c::earth(julian)),
-        "mars" => Some(vsop87c::mars(julian)),
-        "jupiter" => Some(vsop87c::jupiter(julian)),
-        "mercury" => Some(vsop87c::mercury(julian)),
-        "saturn" => Some(vsop87c::saturn(julian)),
-        "uranus" => Some(vsop87c::uranus(julian)),
-        "neptune" => Some(vsop87c::neptune(julian)),
-        _ => None,
-    } {
-        println!("test");
-
+pub fn get_celestial_position<J: Into<JulianDate>>(julian: J, planet_name: &str) -> Result<CelestialPosition, Error> {
+    let date = julian.into();
+    if planet_name.eq_ignore_ascii_case("earth") {
+        check_span(date, EARTH_SPAN)?;
+        let helio_coords = vsop87::vsop87c::earth(date.to_tdb().jd);
         let helio_cart_coords = CartesianCoordinates {
             x: helio_coords.x,
             y: helio_coords.y,
             z: helio_coords.z,
         };
-        let point =
-            CelestialPosition::from_helio_cart(julian, helio_cart_coords, CelestialBodyType::Planet);
-        Ok(point)
+        return Ok(CelestialPosition::from_helio_cart(date, helio_cart_coords, CelestialBodyType::Planet));
+    }
+    match planet_name.parse::<Body>() {
+        Ok(ref body) if body.is_planet() => {
+            check_epoch(body, date)?;
+            Ok(body.position(date))
+        }
+        _ => Err(Error::UnknownBody(planet_name.to_string())),
+    }
+}
+
+/// Years either side of J2000 over which VSOP87 is good to 1" for the Earth
+const EARTH_SPAN: f64 = 4000.0;
+
+/// Checks that `julian` lies within the span over which VSOP87 is good to 1" for `body`:
+/// 4000 years either side of J2000 for Mercury to Mars, 2000 years for Jupiter and Saturn
+/// and 6000 years for Uranus and Neptune (Bretagnon & Francou, 1988). Geocentric positions
+/// need the Earth as well, so no span goes beyond the Earth's.
+pub fn check_epoch<J: Into<JulianDate>>(body: &Body, julian: J) -> Result<(), Error> {
+    let years = match *body {
+        Body::Jupiter | Body::Saturn => 2000.0,
+        _ => EARTH_SPAN,
+    };
+    check_span(julian.into(), years)
+}
+
+fn check_span(date: JulianDate, years: f64) -> Result<(), Error> {
+    if (date.to_tdb().jd - util::J2000).abs() > years * 365.25 {
+        return Err(Error::OutOfRangeEpoch(date.jd));
+    }
+    Ok(())
+}
+
+/// How a planet appears from the Earth at an instant
+#[derive(Serialize,Deserialize,Debug, Clone, Copy, PartialEq)]
+pub struct PhysicalEphemeris {
+    /// Apparent visual magnitude (Mallama & Hilton, 2018)
+    pub magnitude: f64,
+    /// Sun-planet-Earth angle in degrees
+    pub phase_angle: f64,
+    /// Fraction of the disc that is lit, 0 to 1
+    pub illuminated_fraction: f64,
+    /// Apparent equatorial diameter in arcseconds
+    pub diameter: f64,
+    /// Saturnicentric latitude of the Earth referred to the ring plane, in degrees; positive
+    /// when the north face of the rings is seen. Only for Saturn.
+    pub ring_tilt: Option<f64>,
+}
+
+/// Returns the magnitude, phase and apparent size of a planet
+///
+/// # Arguments
+///
+/// * `julian` - Julian day
+/// * `planet_name` - Planet Name
+///
+/// # Example
+///
+/// ```
+/// use astral::planet;
+/// // Venus at greatest eastern elongation, 2017 January 12
+/// let venus = planet::get_physical_ephemeris(2457766.0, "Venus").unwrap();
+/// assert!((venus.magnitude + 4.5).abs() < 0.1);
+/// assert!((venus.illuminated_fraction - 0.5).abs() < 0.05);
+/// assert!((venus.diameter - 24.4).abs() < 0.1);
+/// // Saturn at opposition, 2017 June 15, with its rings nearly fully open
+/// let saturn = planet::get_physical_ephemeris(2457919.5, "Saturn").unwrap();
+/// assert!((saturn.ring_tilt.unwrap() - 26.6).abs() < 0.1);
+/// assert!(saturn.magnitude < 0.2 && saturn.magnitude > -0.3);
+/// ```
+pub fn get_physical_ephemeris<J: Into<JulianDate>>(julian: J, planet_name: &str) -> Result<PhysicalEphemeris, Error> {
+    let date = julian.into();
+    match planet_name.parse::<Body>() {
+        Ok(ref body) if body.is_planet() => {
+            check_epoch(body, date)?;
+            Ok(physical_ephemeris(body, date).unwrap())
+        }
+        _ => Err(Error::UnknownBody(planet_name.to_string())),
+    }
+}
+
+/// Magnitude, phase and apparent size of a planet from its light-time corrected VSOP87C
+/// vectors, or `None` for bodies that are not planets
+pub fn physical_ephemeris<J: Into<JulianDate>>(body: &Body, julian: J) -> Option<PhysicalEphemeris> {
+    let radius = match *body {
+        Body::Mercury => 2440.53,
+        Body::Venus => 6051.8,
+        Body::Mars => 3396.19,
+        Body::Jupiter => 71492.0,
+        Body::Saturn => 60268.0,
+        Body::Uranus => 25559.0,
+        Body::Neptune => 24764.0,
+        _ => return None,
+    };
+    let julian = julian.into().to_tdb().jd;
+    let position = body.position(JulianDate::tdb(julian));
+    let geo = position.light_time_cart.clone().unwrap();
+    let helio = util::helio_frm_geo(julian, geo.clone());
+    let (r, delta) = (util::norm(&helio), util::norm(&geo));
+    let phase_angle = util::angle_between(&helio, &geo).to_degrees();
+
+    let ring_tilt = match *body {
+        Body::Saturn => Some(saturn_ring_tilt(julian, &geo)),
+        _ => None,
+    };
+    let distance_modulus = 5.0 * (r * delta).log10();
+    let magnitude = match *body {
+        Body::Mercury => mercury_magnitude(phase_angle),
+        Body::Venus => venus_magnitude(phase_angle),
+        Body::Mars => mars_magnitude(phase_angle),
+        Body::Jupiter => jupiter_magnitude(phase_angle),
+        Body::Saturn => {
+            let sun_tilt = saturn_ring_tilt(julian, &helio);
+            let tilt = (ring_tilt.unwrap().to_radians().sin() * sun_tilt.to_radians().sin())
+                .abs()
+                .sqrt()
+                .asin();
+            saturn_magnitude(phase_angle, tilt.to_degrees())
+        }
+        Body::Uranus => {
+            let latitude = (uranus_latitude(julian, &geo).abs() + uranus_latitude(julian, &helio).abs()) / 2.0;
+            uranus_magnitude(phase_angle, latitude)
+        }
+        _ => neptune_magnitude(phase_angle, julian),
+    };
+
+    Some(PhysicalEphemeris {
+        magnitude: magnitude + distance_modulus,
+        phase_angle,
+        illuminated_fraction: (1.0 + phase_angle.to_radians().cos()) / 2.0,
+        diameter: 2.0 * (radius / (delta * util::AU_IN_KM)).atan().to_degrees() * 3600.0,
+        ring_tilt,
+    })
+}
+
+// Magnitudes at unit distances from the Sun and the Earth for a phase angle in degrees
+
+fn mercury_magnitude(a: f64) -> f64 {
+    -0.613 +
+        a * (6.3280e-02 + a * (-1.6336e-03 + a * (3.3644e-05 + a * (-3.4265e-07 + a * (1.6893e-09 - a * 3.0334e-12)))))
+}
+
+fn venus_magnitude(a: f64) -> f64 {
+    if a < 163.7 {
+        return -4.384 + a * (-1.044e-03 + a * (3.687e-04 + a * (-2.814e-06 + a * 8.938e-09)));
+    }
+    236.05828 + a * (-2.81914 + a * 8.39034e-03)
+}
+
+/// Without the corrections for rotational and orbital longitude, worth a few hundredths
+fn mars_magnitude(a: f64) -> f64 {
+    if a <= 50.0 {
+        return -1.601 + a * (2.267e-02 - a * 1.302e-04);
+    }
+    -0.367 + a * (-0.02573 + a * 0.0003445)
+}
+
+fn jupiter_magnitude(a: f64) -> f64 {
+    if a <= 12.0 {
+        return -9.395 + a * (-3.7e-04 + a * 6.16e-04);
+    }
+    let x = a / 180.0;
+    -9.428 - 2.5 * (1.0 + x * (-1.507 + x * (-0.363 + x * (-0.062 + x * (2.809 - x * 1.876))))).log10()
+}
+
+/// Globe and rings together, `tilt` being the effective ring tilt in degrees
+fn saturn_magnitude(a: f64, tilt: f64) -> f64 {
+    let sin_tilt = tilt.to_radians().sin();
+    -8.914 - 1.825 * sin_tilt + 2.6e-02 * a - 0.378 * sin_tilt * (-2.25 * a).exp()
+}
+
+/// `latitude` is the mean planetographic latitude of the sub-Earth and sub-solar points
+fn uranus_magnitude(a: f64, latitude: f64) -> f64 {
+    -7.110 - 8.4e-04 * latitude + a * (6.587e-03 + a * 1.045e-04)
+}
+
+fn neptune_magnitude(a: f64, julian: f64) -> f64 {
+    // Neptune brightened between 1980 and 2000
+    let year = 2000.0 + (julian - util::J2000) / 365.25;
+    let base = if year > 2000.0 {
+        -7.00
+    } else if year > 1980.0 {
+        -6.89 - 0.0054 * (year - 1980.0)
     } else {
-        Err("Couldnt find planet.")
+        -6.89
+    };
+    if a > 1.9 {
+        return base + a * (7.944e-03 + a * 9.617e-05);
     }
+    base
+}
+
+/// Saturnicentric latitude in degrees of the origin of `cart` (ecliptic of date, pointing
+/// at Saturn) referred to the ring plane (Meeus, ch. 45)
+fn saturn_ring_tilt(julian: f64, cart: &CartesianCoordinates) -> f64 {
+    let t = (julian - util::J2000) / 36525.0;
+    let inclination = (28.075216 - 0.012998 * t + 0.000004 * t * t).to_radians();
+    let node = Degrees(169.508470 + 1.394681 * t + 0.000412 * t * t);
+    let ecl = util::ecl_frm_cart(cart.x, cart.y, cart.z);
+    let sin_tilt = inclination.sin() * ecl.lat.cos() * (ecl.lng - node).sin() - inclination.cos() * ecl.lat.sin();
+    sin_tilt.asin().to_degrees()
+}
+
+/// Planetographic latitude in degrees of the point of Uranus facing the origin of `cart`
+/// (ecliptic of date, pointing at Uranus), from the IAU pole
+fn uranus_latitude(julian: f64, cart: &CartesianCoordinates) -> f64 {
+    let to_j2000 = precession::multiply(
+        &precession::transpose(&precession::precession_matrix(julian)),
+        &precession::ecliptic_matrix(precession::mean_obliquity(julian)),
+    );
+    let direction = precession::apply(&to_j2000, cart);
+    let pole = util::cart_frm_eq(EqCoordinates {
+        ra: Degrees(257.311),
+        dec: Degrees(-15.175),
+        dist: 1.0,
+    });
+    let toward_origin = CartesianCoordinates {
+        x: -direction.x,
+        y: -direction.y,
+        z: -direction.z,
+    };
+    90.0 - util::angle_between(&pole, &toward_origin).to_degrees()
 }
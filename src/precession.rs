@@ -0,0 +1,127 @@
+//! Precession (IAU 2006) and nutation (IAU 1980, through the `astro` crate) as rotation
+//! matrices acting on equatorial cartesian coordinates.
+use super::*;
+use coords::*;
+
+pub type Matrix = [[f64; 3]; 3];
+
+const ARCSEC_IN_RAD: f64 = std::f64::consts::PI / (180.0 * 3600.0);
+
+/// Which equator and equinox equatorial coordinates are referred to
+#[derive(Serialize,Deserialize,Debug, Clone, Copy, PartialEq)]
+pub enum Equinox {
+    /// Mean equator and equinox of J2000.0, the frame of the star catalogue
+    J2000,
+    /// Mean equator and equinox of date: precession only
+    MeanOfDate,
+    /// True equator and equinox of date: precession and nutation
+    TrueOfDate,
+}
+
+/// Julian centuries of TT since J2000.0
+fn centuries(julian: f64) -> f64 {
+    (julian - util::J2000) / 36525.0
+}
+
+/// Mean obliquity of the ecliptic (IAU 2006), radians
+pub fn mean_obliquity(julian: f64) -> f64 {
+    let t = centuries(julian);
+    let arcsec = 84381.406 +
+        t * (-46.836769 + t * (-0.0001831 + t * (0.00200340 + t * (-0.000000576 - t * 0.0000000434))));
+    arcsec * ARCSEC_IN_RAD
+}
+
+/// Nutation in longitude and in obliquity, radians
+pub fn nutation(julian: f64) -> (f64, f64) {
+    astro::nutation::nutation(julian)
+}
+
+/// True obliquity of the ecliptic, radians
+pub fn true_obliquity(julian: f64) -> f64 {
+    let (_, nut_in_oblq) = nutation(julian);
+    mean_obliquity(julian) + nut_in_oblq
+}
+
+/// Rotates J2000 mean equatorial coordinates to the mean equator and equinox of date
+/// (IAU 2006 precession angles)
+pub fn precession_matrix(julian: f64) -> Matrix {
+    let t = centuries(julian);
+    let zeta = (2.650545 +
+        t * (2306.083227 + t * (0.2988499 + t * (0.01801828 + t * (-0.000005971 - t * 0.0000003173))))) *
+        ARCSEC_IN_RAD;
+    let z = (-2.650545 +
+        t * (2306.077181 + t * (1.0927348 + t * (0.01826837 + t * (-0.000028596 - t * 0.0000002904))))) *
+        ARCSEC_IN_RAD;
+    let theta = (t * (2004.191903 + t * (-0.4294934 + t * (-0.04182264 + t * (-0.000007089 - t * 0.0000001274))))) *
+        ARCSEC_IN_RAD;
+    multiply(&rot_z(-z), &multiply(&rot_y(theta), &rot_z(-zeta)))
+}
+
+/// Rotates mean equatorial coordinates of date to true equatorial coordinates of date
+pub fn nutation_matrix(julian: f64) -> Matrix {
+    let (nut_in_long, nut_in_oblq) = nutation(julian);
+    let oblq = mean_obliquity(julian);
+    multiply(
+        &rot_x(-(oblq + nut_in_oblq)),
+        &multiply(&rot_z(-nut_in_long), &rot_x(oblq)),
+    )
+}
+
+/// Rotates J2000 mean equatorial coordinates to the requested equinox
+pub fn equinox_matrix(julian: f64, equinox: Equinox) -> Matrix {
+    match equinox {
+        Equinox::J2000 => IDENTITY,
+        Equinox::MeanOfDate => precession_matrix(julian),
+        Equinox::TrueOfDate => multiply(&nutation_matrix(julian), &precession_matrix(julian)),
+    }
+}
+
+/// Rotates ecliptic coordinates to equatorial coordinates for an obliquity in radians
+pub fn ecliptic_matrix(obliquity: f64) -> Matrix {
+    rot_x(-obliquity)
+}
+
+pub const IDENTITY: Matrix = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+
+pub fn rot_x(angle: f64) -> Matrix {
+    let (s, c) = angle.sin_cos();
+    [[1.0, 0.0, 0.0], [0.0, c, s], [0.0, -s, c]]
+}
+
+pub fn rot_y(angle: f64) -> Matrix {
+    let (s, c) = angle.sin_cos();
+    [[c, 0.0, -s], [0.0, 1.0, 0.0], [s, 0.0, c]]
+}
+
+pub fn rot_z(angle: f64) -> Matrix {
+    let (s, c) = angle.sin_cos();
+    [[c, s, 0.0], [-s, c, 0.0], [0.0, 0.0, 1.0]]
+}
+
+pub fn multiply(a: &Matrix, b: &Matrix) -> Matrix {
+    let mut m = [[0.0; 3]; 3];
+    for i in 0..3 {
+        for j in 0..3 {
+            m[i][j] = a[i][0] * b[0][j] + a[i][1] * b[1][j] + a[i][2] * b[2][j];
+        }
+    }
+    m
+}
+
+pub fn transpose(a: &Matrix) -> Matrix {
+    let mut m = [[0.0; 3]; 3];
+    for i in 0..3 {
+        for j in 0..3 {
+            m[i][j] = a[j][i];
+        }
+    }
+    m
+}
+
+pub fn apply(m: &Matrix, v: &CartesianCoordinates) -> CartesianCoordinates {
+    CartesianCoordinates {
+        x: m[0][0] * v.x + m[0][1] * v.y + m[0][2] * v.z,
+        y: m[1][0] * v.x + m[1][1] * v.y + m[1][2] * v.z,
+        z: m[2][0] * v.x + m[2][1] * v.y + m[2][2] * v.z,
+    }
+}
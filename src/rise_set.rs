@@ -0,0 +1,153 @@
+use super::*;
+use coords::*;
+use precession::Equinox;
+
+/// Geometric altitude of a star's centre when it appears on the horizon, allowing for
+/// the standard 34' of refraction
+pub const STAR_HORIZON_ALTITUDE: f64 = -0.5667;
+/// Geometric altitude used for planets
+pub const PLANET_HORIZON_ALTITUDE: f64 = 0.0;
+/// Mean geocentric altitude of the Moon's centre at moonrise (Meeus, ch. 15)
+pub const MOON_HORIZON_ALTITUDE: f64 = 0.125;
+
+const SAMPLE_STEP: f64 = 1.0 / 96.0; // 15 minutes
+const PRECISION: f64 = 1.0 / 86400.0; // 1 second
+
+/// Returns the rises, upper culminations and sets of a body
+///
+/// # Arguments
+///
+/// * `body` - Body to follow
+/// * `location` - Observer location
+/// * `start` - Julian day the search starts from
+/// * `end` - Julian day the search ends at
+/// * `altitude` - Target altitude in degrees, defaults to the body's horizon when `None`
+///
+/// # Example
+///
+/// ```
+/// use astral::{rise_set, Body};
+/// use astral::coords::{Location, HorizonEventKind};
+/// let location = Location::new(38.44043, -122.71405).unwrap();
+/// let events = rise_set::find_events(&Body::Jupiter, location, 2458061.0, 2458062.0, None);
+/// assert!(events.iter().any(|event| event.kind == HorizonEventKind::Rise));
+/// ```
+pub fn find_events(
+    body: &Body,
+    location: Location,
+    start: f64,
+    end: f64,
+    altitude: Option<f64>,
+) -> Vec<HorizonEvent> {
+    let target = altitude.unwrap_or_else(|| horizon_altitude(body, location));
+    find_events_with(|julian| body.position(julian), location, start, end, target)
+}
+
+/// Geometric altitude (degrees) of the body's centre when it appears on the horizon,
+/// adjusted for the location's atmosphere and the dip of the horizon from its elevation
+pub fn horizon_altitude(body: &Body, location: Location) -> f64 {
+    let standard = match *body {
+        Body::Sun => sun::SUNRISE_ALTITUDE,
+        Body::Moon => MOON_HORIZON_ALTITUDE,
+        Body::Star(_) => STAR_HORIZON_ALTITUDE,
+        _ => return PLANET_HORIZON_ALTITUDE - location.horizon_dip(),
+    };
+    let refraction_change = util::HORIZON_REFRACTION * (location.refraction_scale() - 1.0);
+    standard - refraction_change - location.horizon_dip()
+}
+
+/// Returns the crossings of `altitude` (degrees, geometric) and the upper culminations
+/// of whatever body `position` describes, in chronological order. Positions are taken
+/// as true of date.
+pub fn find_events_with<F>(
+    position: F,
+    location: Location,
+    start: f64,
+    end: f64,
+    altitude: f64,
+) -> Vec<HorizonEvent>
+where
+    F: Fn(f64) -> CelestialPosition,
+{
+    let hz = |julian: f64| position(julian).get_true_hz_coords_at(location, Equinox::TrueOfDate);
+    let height = |julian: f64| hz(julian).alt.0 - altitude;
+    let event = |kind: HorizonEventKind, julian: f64| {
+        let coords = hz(julian);
+        HorizonEvent {
+            kind,
+            julian_day: julian,
+            az: coords.az,
+            alt: coords.alt,
+        }
+    };
+
+    let mut events = vec![];
+    let mut t0 = start;
+    let mut h0 = height(t0);
+    // seed the slope from a sample before `start`, so a culmination within the first step
+    // is not missed
+    let mut rising = h0 > height(start - SAMPLE_STEP);
+    while t0 < end {
+        let t1 = (t0 + SAMPLE_STEP).min(end);
+        let h1 = height(t1);
+
+        if h0 < 0.0 && h1 >= 0.0 {
+            events.push(event(HorizonEventKind::Rise, bisect(&height, t0, t1)));
+        } else if h0 >= 0.0 && h1 < 0.0 {
+            events.push(event(HorizonEventKind::Set, bisect(&height, t0, t1)));
+        }
+
+        // an upper culmination sits between the last rising sample and the first falling one
+        let is_rising = h1 > h0;
+        if rising && !is_rising {
+            let peak = golden_section_max(&height, t0 - SAMPLE_STEP, t1);
+            if peak >= start && peak < end {
+                events.push(event(HorizonEventKind::Culmination, peak));
+            }
+        }
+        rising = is_rising;
+
+        t0 = t1;
+        h0 = h1;
+    }
+    events.sort_by(|a, b| a.julian_day.total_cmp(&b.julian_day));
+    events
+}
+
+/// Root of `f` between `lo` and `hi`, which must bracket a sign change
+pub(crate) fn bisect<F: Fn(f64) -> f64>(f: &F, mut lo: f64, mut hi: f64) -> f64 {
+    let lo_sign = f(lo) < 0.0;
+    while hi - lo > PRECISION {
+        let mid = (lo + hi) / 2.0;
+        if (f(mid) < 0.0) == lo_sign {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+    (lo + hi) / 2.0
+}
+
+/// Maximum of `f` between `lo` and `hi`, which must bracket a single peak
+pub(crate) fn golden_section_max<F: Fn(f64) -> f64>(f: &F, mut lo: f64, mut hi: f64) -> f64 {
+    let ratio = (5.0f64.sqrt() - 1.0) / 2.0;
+    let mut a = hi - ratio * (hi - lo);
+    let mut b = lo + ratio * (hi - lo);
+    let (mut fa, mut fb) = (f(a), f(b));
+    while hi - lo > PRECISION {
+        if fa > fb {
+            hi = b;
+            b = a;
+            fb = fa;
+            a = hi - ratio * (hi - lo);
+            fa = f(a);
+        } else {
+            lo = a;
+            a = b;
+            fa = fb;
+            b = lo + ratio * (hi - lo);
+            fb = f(b);
+        }
+    }
+    (lo + hi) / 2.0
+}
@@ -0,0 +1,78 @@
+//! Sidereal time: the hour angle of the equinox, in degrees within [0, 360)
+use super::*;
+use coords::Location;
+use time::JulianDate;
+use units::Degrees;
+
+/// Greenwich mean sidereal time in degrees for a Julian day of UT1, from the Earth
+/// rotation angle (IAU 2006)
+///
+/// # Example
+///
+/// ```
+/// use astral::sidereal;
+/// // IERS Conventions (2010) / SOFA iauGmst06 test case, UT1 = TT = MJD 53736
+/// let gmst = sidereal::gmst(2453736.5).to_radians();
+/// assert!((gmst.0 - 1.754174971870091203).abs() < 1e-9);
+/// ```
+pub fn gmst(jd_ut1: f64) -> Degrees {
+    let tt = JulianDate::ut1(jd_ut1).to_tt().jd;
+    let t = (tt - util::J2000) / 36525.0;
+    let du = jd_ut1 - util::J2000;
+    // Earth rotation angle, with the whole turns of each day dropped first to keep precision
+    let era = 360.0 * (du.fract() + 0.7790572732640 + 0.00273781191135448 * du);
+    let arcsec = 0.014506 +
+        t * (4612.156534 + t * (1.3915817 + t * (-0.00000044 + t * (-0.000029956 - t * 0.0000000368))));
+    Degrees(era + arcsec / 3600.0).normalized()
+}
+
+/// Greenwich apparent sidereal time in degrees: GMST plus the equation of the equinoxes.
+/// Bare Julian days are UTC.
+///
+/// # Example
+///
+/// ```
+/// use astral::sidereal;
+/// use astral::time::JulianDate;
+/// // SOFA iauGst06a test case, UT1 = TT = MJD 53736, to the precision of IAU 1980 nutation
+/// let gast = sidereal::gast(JulianDate::ut1(2453736.5)).to_radians();
+/// assert!((gast.0 - 1.754166137675019159).abs() < 5e-8);
+/// ```
+pub fn gast<J: Into<JulianDate>>(julian: J) -> Degrees {
+    let date = julian.into();
+    (gmst(date.to_ut1().jd) + equation_of_equinoxes(date.to_tt().jd)).normalized()
+}
+
+/// Local apparent sidereal time in degrees, GAST plus the east longitude of the location
+///
+/// # Example
+///
+/// ```
+/// use astral::sidereal;
+/// use astral::coords::Location;
+/// let greenwich = Location::new(51.4779, 0.0).unwrap();
+/// let santa_rosa = Location::new(38.44043, -122.71405).unwrap();
+/// let julian = 2458061.2743171295;
+/// assert_eq!(sidereal::local_sidereal_time(julian, greenwich), sidereal::gast(julian));
+/// let difference = sidereal::local_sidereal_time(julian, santa_rosa) - sidereal::gast(julian);
+/// assert!((difference.signed().0 + 122.71405).abs() < 1e-9);
+/// ```
+pub fn local_sidereal_time<J: Into<JulianDate>>(julian: J, location: Location) -> Degrees {
+    (gast(julian) + Degrees(location.lon)).normalized()
+}
+
+/// Local mean sidereal time in degrees, GMST plus the east longitude of the location
+pub fn local_mean_sidereal_time<J: Into<JulianDate>>(julian: J, location: Location) -> Degrees {
+    (gmst(julian.into().to_ut1().jd) + Degrees(location.lon)).normalized()
+}
+
+/// Equation of the equinoxes in degrees, the nutation in right ascension, for a Julian
+/// day of TT
+pub fn equation_of_equinoxes(jd_tt: f64) -> Degrees {
+    let t = (jd_tt - util::J2000) / 36525.0;
+    let (nut_in_long, _) = precession::nutation(jd_tt);
+    let node = (125.04452 - 1934.136261 * t).to_radians();
+    // complementary terms of the IAU 1994 definition
+    let arcsec = 0.00264096 * node.sin() + 0.00006352 * (2.0 * node).sin();
+    Degrees((nut_in_long * precession::true_obliquity(jd_tt).cos()).to_degrees() + arcsec / 3600.0)
+}
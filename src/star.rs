@@ -1,9 +1,27 @@
 extern crate csv;
 use super::*;
 use coords::*;
+use error::Error;
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::io;
+use std::path::Path;
+use std::slice;
+use time::JulianDate;
+use std::f64::consts::PI;
 
-#[derive(Debug, Clone, Deserialize)]
+lazy_static! {
+    static ref EMBEDDED_CATALOG: StarCatalog = {
+        let star_csv_string = include_str!("../data/star_data.csv");
+        StarCatalog::from_reader(star_csv_string.as_bytes()).expect("embedded star catalogue is valid")
+    };
+}
+
+#[derive(Serialize,Deserialize,Debug, Clone, PartialEq)]
 pub struct StarData {
+    /// HYG database id, when the catalogue provides one
+    #[serde(default)]
+    pub id: Option<u64>,
     pub proper: String,
     pub x: f64,
     pub y: f64,
@@ -11,8 +29,60 @@ pub struct StarData {
     pub dist: f64,
     pub ra: f64,
     pub dec: f64,
+    /// Hipparcos catalogue number
+    #[serde(default)]
+    pub hip: Option<u64>,
+    /// Henry Draper catalogue number
+    #[serde(default)]
+    pub hd: Option<u64>,
+    /// Harvard Revised / Yale Bright Star catalogue number
+    #[serde(default)]
+    pub hr: Option<u64>,
+    /// Gliese catalogue designation
+    #[serde(default)]
+    pub gl: Option<String>,
+    /// Combined Bayer/Flamsteed designation, e.g. "21Alp And"
+    #[serde(default)]
+    pub bf: Option<String>,
+    /// Abbreviated Bayer letter, e.g. "Alp"
+    #[serde(default)]
+    pub bayer: Option<String>,
+    /// Flamsteed number
+    #[serde(default)]
+    pub flam: Option<u32>,
+    /// Constellation abbreviation, e.g. "And"
+    #[serde(default)]
+    pub con: Option<String>,
+    /// Apparent visual magnitude
+    #[serde(default)]
+    pub mag: Option<f64>,
+    /// Absolute visual magnitude
+    #[serde(default)]
+    pub absmag: Option<f64>,
+    /// B-V colour index
+    #[serde(default)]
+    pub ci: Option<f64>,
+    /// Spectral type, e.g. "B8IVmnp..."
+    #[serde(default)]
+    pub spect: Option<String>,
+    /// Luminosity as a multiple of the Sun's
+    #[serde(default)]
+    pub lum: Option<f64>,
+    /// Proper motion in right ascension (multiplied by cos dec), milliarcseconds per year
+    #[serde(default)]
+    pub pmra: Option<f64>,
+    /// Proper motion in declination, milliarcseconds per year
+    #[serde(default)]
+    pub pmdec: Option<f64>,
+    /// Radial velocity, km/s
+    #[serde(default)]
+    pub rv: Option<f64>,
 }
 
+const MAS_IN_RAD: f64 = PI / (180.0 * 3600.0 * 1000.0);
+const KM_PER_S_IN_PC_PER_YEAR: f64 = 1.0 / 977792.2;
+const DAYS_IN_JULIAN_YEAR: f64 = 365.25;
+
 /// Returns the CelestialPosition of a star
 ///
 /// # Arguments
@@ -28,42 +98,403 @@ pub struct StarData {
 /// assert_eq!(star::get_celestial_position(jd,"Polaris").unwrap().julian_day,jd);
 /// assert_eq!(star::get_celestial_position(jd,"polaris").unwrap().julian_day,jd);
 /// ```
-pub fn get_celestial_position(julian: f64, name: &str) -> Result<CelestialPosition, &'static str> {
-    match star::get_data(name.to_string()) {
-        Ok(star_eq) => {
-            let position =  CelestialPosition {
-                geo_cart: CartesianCoordinates {
-                    x: star_eq.x,
-                    y: star_eq.y,
-                    z: star_eq.z,
-                },
-                object_type: CelestialBodyType::Star,
-                julian_day: julian,
-            };
-            Ok(position)
-        },
-        Err(err) => Err(err),
+pub fn get_celestial_position<J: Into<JulianDate>>(julian: J, name: &str) -> Result<CelestialPosition, Error> {
+    let star_data = star::get_data(name.to_string())?;
+    Ok(star_data.get_celestial_position(julian))
+}
+
+impl StarData {
+    /// Position of the star at `julian`, propagated from the catalogue's J2000 position
+    /// along its space motion
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use astral::star::StarCatalog;
+    /// let csv = "proper,ra,dec,dist,x,y,z,pmra,pmdec,rv
+    /// Barnard's Star,17.963472,4.693388,1.8238,-0.017373,-1.816613,0.149123,-798.58,10328.12,-110.6
+    /// ";
+    /// let catalog = StarCatalog::from_reader(csv.as_bytes()).unwrap();
+    /// let barnard = catalog.get("Barnard's Star").unwrap();
+    /// let j2000 = barnard.get_celestial_position(2451545.0).get_eq_coords();
+    /// let j2100 = barnard.get_celestial_position(2451545.0 + 36525.0).get_eq_coords();
+    /// // a little over ten arcseconds a year, mostly northwards
+    /// assert!(((j2100.dec - j2000.dec).0 - 0.287).abs() < 0.002);
+    /// ```
+    pub fn get_celestial_position<J: Into<JulianDate>>(&self, julian: J) -> CelestialPosition {
+        let julian = julian.into().to_utc().jd;
+        let years = (julian - util::J2000) / DAYS_IN_JULIAN_YEAR;
+        let velocity = self.space_velocity();
+        CelestialPosition {
+            geo_cart: CartesianCoordinates {
+                x: self.x + velocity.x * years,
+                y: self.y + velocity.y * years,
+                z: self.z + velocity.z * years,
+            },
+            object_type: CelestialBodyType::Star,
+            julian_day: julian,
+            light_time_cart: None,
+        }
+    }
+
+    /// Space velocity in parsecs per year (equatorial J2000 axes), from the proper motion,
+    /// radial velocity and distance. Missing values count as zero.
+    pub fn space_velocity(&self) -> CartesianCoordinates {
+        let (ra, dec) = (HourAngle(self.ra).to_radians().0, self.dec.to_radians());
+        let pmra = self.pmra.unwrap_or(0.0) * MAS_IN_RAD * self.dist;
+        let pmdec = self.pmdec.unwrap_or(0.0) * MAS_IN_RAD * self.dist;
+        let rv = self.rv.unwrap_or(0.0) * KM_PER_S_IN_PC_PER_YEAR;
+        CartesianCoordinates {
+            x: rv * dec.cos() * ra.cos() - pmra * ra.sin() - pmdec * dec.sin() * ra.cos(),
+            y: rv * dec.cos() * ra.sin() + pmra * ra.cos() - pmdec * dec.sin() * ra.sin(),
+            z: rv * dec.sin() + pmdec * dec.cos(),
+        }
+    }
+
+    /// Harvard spectral class letter (O, B, A, F, G, K, M, ...) taken from `spect`
+    pub fn spectral_class(&self) -> Option<char> {
+        self.spect
+            .as_ref()
+            .and_then(|spect| spect.chars().find(|c| c.is_ascii_uppercase()))
+    }
+
+    /// Effective temperature in kelvin estimated from the B-V colour index
+    /// (Ballesteros, 2012), handy for picking a render colour
+    pub fn temperature(&self) -> Option<f64> {
+        self.ci.map(|ci| 4600.0 * (1.0 / (0.92 * ci + 1.7) + 1.0 / (0.92 * ci + 0.62)))
+    }
+
+    /// Horizontal coordinates of the star for an observer
+    pub fn get_hz_coords(&self, julian: f64, location: Location) -> HzCoordinates {
+        self.get_celestial_position(julian).get_hz_coords(location)
+    }
+
+    /// Proper name if the star has one, otherwise its best known catalogue designation
+    pub fn name(&self) -> String {
+        if !self.proper.is_empty() {
+            return self.proper.clone();
+        }
+        self.designations()
+            .into_iter()
+            .next()
+            .unwrap_or_else(|| format!("HYG {}", self.id.unwrap_or(0)))
+    }
+
+    /// Every name the star can be looked up by: proper name, Bayer and Flamsteed
+    /// designations, and HIP, HD, HR and Gliese numbers
+    pub fn designations(&self) -> Vec<String> {
+        let mut names = vec![];
+        if !self.proper.is_empty() {
+            names.push(self.proper.clone());
+        }
+        if let Some(ref con) = self.con {
+            if let Some(ref bayer) = self.bayer {
+                names.push(format!("{} {}", bayer, con));
+                if let Some(letter) = greek_letter(bayer) {
+                    names.push(format!("{} {}", letter, con));
+                }
+            }
+            if let Some(flam) = self.flam {
+                names.push(format!("{} {}", flam, con));
+            }
+        }
+        if let Some(ref bf) = self.bf {
+            names.push(bf.clone());
+        }
+        if let Some(hip) = self.hip {
+            names.push(format!("HIP {}", hip));
+        }
+        if let Some(hd) = self.hd {
+            names.push(format!("HD {}", hd));
+        }
+        if let Some(hr) = self.hr {
+            names.push(format!("HR {}", hr));
+        }
+        if let Some(ref gl) = self.gl {
+            names.push(gl.clone());
+        }
+        names
     }
 }
 
-pub fn get_data(name: String) -> Result<StarData, &'static str> {
-    let mut stars: Vec<StarData> = vec![];
-    let star_csv_string = include_str!("../data/star_data.csv");
-    let mut rdr = csv::ReaderBuilder::new().has_headers(true).from_reader(
-        star_csv_string
-            .as_bytes(),
-    );
-    for result in rdr.deserialize() {
-        let record: StarData = result.unwrap();
-        stars.push(record.clone());
-    }
-    let found_star = stars.into_iter().find(|ref mut item| {
-        item.proper.to_lowercase() == name.to_lowercase()
-    });
-    if let Some(star) = found_star {
-        Ok(star)
-    } else{
-        Err("No star with that name found")
-    }
-    // return star.unwrap();
+pub fn get_data(name: String) -> Result<StarData, Error> {
+    match StarCatalog::embedded().get(&name) {
+        Some(star) => Ok(star.clone()),
+        None => Err(Error::UnknownBody(name)),
+    }
+}
+
+/// A star catalogue indexed by normalized name and by HYG id
+///
+/// # Example
+///
+/// ```
+/// use astral::star::StarCatalog;
+/// let catalog = StarCatalog::embedded();
+/// assert_eq!(catalog.get("kapteyns star").unwrap().proper, "Kapteyn's Star");
+/// assert!(catalog.search_prefix("Al").iter().any(|star| star.proper == "Aldebaran"));
+/// assert_eq!(catalog.search_fuzzy("Betelgeuze", 2)[0].proper, "Betelgeuse");
+/// ```
+#[derive(Debug, Clone)]
+pub struct StarCatalog {
+    stars: Vec<StarData>,
+    by_name: BTreeMap<String, usize>,
+    by_id: HashMap<u64, usize>,
+}
+
+impl StarCatalog {
+    /// The named stars shipped with the crate, parsed on first use
+    pub fn embedded() -> &'static StarCatalog {
+        &EMBEDDED_CATALOG
+    }
+
+    /// Parses a catalogue in the HYG v3 CSV format. Only `proper, x, y, z, dist, ra, dec`
+    /// are required; any other HYG column is optional.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use astral::star::StarCatalog;
+    /// let csv = "id,hip,hd,hr,bf,proper,ra,dec,dist,mag,spect,x,y,z,bayer,flam,con
+    /// 676,677,358,15,21Alp And,Alpheratz,0.139791,29.090432,29.7442,2.070,B8IVmnp...,25.974572,0.951042,14.461264,Alp,21,And
+    /// ";
+    /// let catalog = StarCatalog::from_reader(csv.as_bytes()).unwrap();
+    /// let alpheratz = catalog.get("HIP 677").unwrap();
+    /// assert_eq!(catalog.get("alpha and").unwrap(), alpheratz);
+    /// assert_eq!(catalog.get("21 And").unwrap(), alpheratz);
+    /// assert_eq!(catalog.get_by_id(676).unwrap().mag, Some(2.07));
+    /// ```
+    pub fn from_reader<R: io::Read>(reader: R) -> Result<StarCatalog, Error> {
+        let mut rdr = csv::ReaderBuilder::new().has_headers(true).from_reader(reader);
+        let mut stars = vec![];
+        for (index, result) in rdr.deserialize().enumerate() {
+            let record: StarData = result.map_err(|err| {
+                Error::CatalogParse {
+                    row: index + 1,
+                    message: err.to_string(),
+                }
+            })?;
+            stars.push(record);
+        }
+        Ok(StarCatalog::from_stars(stars))
+    }
+
+    /// Loads a catalogue file such as the full `hygdata_v3.csv`
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<StarCatalog, Error> {
+        let file = File::open(path).map_err(|err| Error::Io(err.to_string()))?;
+        StarCatalog::from_reader(io::BufReader::new(file))
+    }
+
+    pub fn from_stars(stars: Vec<StarData>) -> StarCatalog {
+        let mut by_name = BTreeMap::new();
+        let mut by_id = HashMap::new();
+        for (index, star) in stars.iter().enumerate() {
+            for name in star.designations() {
+                by_name.entry(normalize_name(&name)).or_insert(index);
+            }
+            if let Some(id) = star.id {
+                by_id.entry(id).or_insert(index);
+            }
+        }
+        StarCatalog {
+            stars,
+            by_name,
+            by_id,
+        }
+    }
+
+    /// Looks a star up by name or designation, ignoring case, spaces and punctuation
+    pub fn get(&self, name: &str) -> Option<&StarData> {
+        self.by_name.get(&normalize_name(name)).map(|&index| &self.stars[index])
+    }
+
+    pub fn get_by_id(&self, id: u64) -> Option<&StarData> {
+        self.by_id.get(&id).map(|&index| &self.stars[index])
+    }
+
+    pub fn iter(&self) -> slice::Iter<'_, StarData> {
+        self.stars.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.stars.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.stars.is_empty()
+    }
+
+    /// Stars with a known apparent magnitude brighter (smaller) than `mag`
+    pub fn brighter_than(&self, mag: f64) -> Vec<&StarData> {
+        self.stars
+            .iter()
+            .filter(|star| star.mag.is_some_and(|star_mag| star_mag < mag))
+            .collect()
+    }
+
+    /// Stars brighter than `mag` that are above the horizon for `location` at `julian`,
+    /// brightest first
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use astral::star::StarCatalog;
+    /// use astral::coords::Location;
+    /// let csv = "proper,ra,dec,dist,mag,x,y,z
+    /// Polaris,2.529750,89.264109,132.6260,1.970,3.018068,1.160287,132.588878
+    /// Sirius,6.752481,-16.716116,2.6371,-1.440,-0.494323,2.476731,-0.758485
+    /// ";
+    /// let catalog = StarCatalog::from_reader(csv.as_bytes()).unwrap();
+    /// let location = Location::new(38.44043, -122.71405).unwrap();
+    /// let visible = catalog.visible_brighter_than(3.0, location, 2458061.2743171295);
+    /// assert_eq!(visible.len(), 1);
+    /// assert_eq!(visible[0].proper, "Polaris");
+    /// ```
+    pub fn visible_brighter_than(&self, mag: f64, location: Location, julian: f64) -> Vec<&StarData> {
+        let mut stars: Vec<&StarData> = self.brighter_than(mag)
+            .into_iter()
+            .filter(|star| star.get_hz_coords(julian, location).alt > Degrees(0.0))
+            .collect();
+        stars.sort_by(|a, b| a.mag.unwrap_or(f64::INFINITY).total_cmp(&b.mag.unwrap_or(f64::INFINITY)));
+        stars
+    }
+
+    /// The `count` stars closest on the sky to `position`, nearest first, with their
+    /// separations. Stars whose separation cannot be computed (NaN) are left out.
+    ///
+    /// Every star's position is propagated to `position.julian_day` on each call, so the
+    /// cost grows with the size of the catalogue.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use astral::Body;
+    /// use astral::star::StarCatalog;
+    /// // Jupiter passed two and a half degrees from Regulus in 2015 August
+    /// let jupiter = Body::Jupiter.position(2457234.5);
+    /// let closest = StarCatalog::embedded().closest_to(&jupiter, 3);
+    /// assert_eq!(closest.len(), 3);
+    /// assert_eq!(closest[0].0.proper, "Regulus");
+    /// assert!(closest[0].1 < closest[1].1);
+    /// ```
+    pub fn closest_to(&self, position: &CelestialPosition, count: usize) -> Vec<(&StarData, Degrees)> {
+        let mut stars: Vec<(&StarData, Degrees)> = self.stars
+            .iter()
+            .filter(|star| star.dist > 0.0)
+            .map(|star| {
+                let separation = star.get_celestial_position(position.julian_day).separation(position);
+                (star, separation)
+            })
+            .filter(|&(_, separation)| !separation.0.is_nan())
+            .collect();
+        stars.sort_by(|a, b| a.1 .0.total_cmp(&b.1 .0));
+        stars.truncate(count);
+        stars
+    }
+
+    /// Stars with a name or designation starting with `prefix`, in alphabetical order
+    pub fn search_prefix(&self, prefix: &str) -> Vec<&StarData> {
+        let prefix = normalize_name(prefix);
+        let mut indices: Vec<usize> = vec![];
+        for (_, &index) in self.by_name
+            .range(prefix.clone()..)
+            .take_while(|&(name, _)| name.starts_with(&prefix))
+        {
+            if !indices.contains(&index) {
+                indices.push(index);
+            }
+        }
+        indices.into_iter().map(|index| &self.stars[index]).collect()
+    }
+
+    /// Stars with a name or designation within `max_distance` edits of `query`, closest first
+    ///
+    /// This scans every name and designation in the catalogue. Names whose length differs
+    /// from the query's by more than `max_distance` are skipped without computing an edit
+    /// distance, but the rest cost O(n·m) each, so prefer `get` or `search_prefix` on
+    /// large catalogues such as the full HYG one.
+    pub fn search_fuzzy(&self, query: &str, max_distance: usize) -> Vec<&StarData> {
+        let query = normalize_name(query);
+        let query_len = query.chars().count();
+        let mut distances: HashMap<usize, usize> = HashMap::new();
+        for (name, &index) in self.by_name.iter() {
+            let name_len = name.chars().count();
+            if name_len.max(query_len) - name_len.min(query_len) > max_distance {
+                continue;
+            }
+            let distance = levenshtein(&query, name);
+            if distance <= max_distance {
+                let best = distances.entry(index).or_insert(distance);
+                *best = (*best).min(distance);
+            }
+        }
+        let mut matches: Vec<(usize, usize)> = distances
+            .into_iter()
+            .map(|(index, distance)| (distance, index))
+            .collect();
+        matches.sort();
+        matches.into_iter().map(|(_, index)| &self.stars[index]).collect()
+    }
+}
+
+impl<'a> IntoIterator for &'a StarCatalog {
+    type Item = &'a StarData;
+    type IntoIter = slice::Iter<'a, StarData>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.stars.iter()
+    }
+}
+
+fn normalize_name(name: &str) -> String {
+    name.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(|c| c.to_lowercase())
+        .collect()
+}
+
+fn greek_letter(abbreviation: &str) -> Option<&'static str> {
+    let letter = match abbreviation.trim_end_matches(|c: char| c.is_numeric() || c == '-') {
+        "Alp" => "Alpha",
+        "Bet" => "Beta",
+        "Gam" => "Gamma",
+        "Del" => "Delta",
+        "Eps" => "Epsilon",
+        "Zet" => "Zeta",
+        "Eta" => "Eta",
+        "The" => "Theta",
+        "Iot" => "Iota",
+        "Kap" => "Kappa",
+        "Lam" => "Lambda",
+        "Mu" => "Mu",
+        "Nu" => "Nu",
+        "Xi" => "Xi",
+        "Omi" => "Omicron",
+        "Pi" => "Pi",
+        "Rho" => "Rho",
+        "Sig" => "Sigma",
+        "Tau" => "Tau",
+        "Ups" => "Upsilon",
+        "Phi" => "Phi",
+        "Chi" => "Chi",
+        "Psi" => "Psi",
+        "Ome" => "Omega",
+        _ => return None,
+    };
+    Some(letter)
+}
+
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..b.len() + 1).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut previous = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous + if ca == *cb { 0 } else { 1 };
+            previous = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(previous + 1);
+        }
+    }
+    row[b.len()]
 }
@@ -1,9 +1,13 @@
 use vsop87::vsop87c;
 use util::*;
 use coords::*;
+use time::{self, JulianDate};
+use sidereal;
+use units::{Degrees, Radians};
 
-pub fn get_celestial_position(julian: f64) -> CelestialPosition {
-    let helio_coords = vsop87c::earth(julian);
+pub fn get_celestial_position<J: Into<JulianDate>>(julian: J) -> CelestialPosition {
+    let date = julian.into();
+    let helio_coords = vsop87c::earth(date.to_tdb().jd);
     let helio_cart_coords = CartesianCoordinates {
         x: -helio_coords.x,
         y: -helio_coords.y,
@@ -12,7 +16,8 @@ pub fn get_celestial_position(julian: f64) -> CelestialPosition {
     let point = CelestialPosition {
         geo_cart: helio_cart_coords,
         object_type: CelestialBodyType::Sun,
-        julian_day: julian,
+        julian_day: date.to_utc().jd,
+        light_time_cart: None,
     };
     return point;
 }
@@ -27,22 +32,90 @@ pub fn sun_coords(d: f64) -> Coords {
     let L = ecliptic_longitude(M);
 
     return Coords {
-        dec: declination(L, 0.0),
-        ra: right_ascension(L, 0.0),
+        dec: Radians(declination(L, 0.0)),
+        ra: Radians(right_ascension(L, 0.0)),
         dist: 149598000.0,
     };
 }
 
+/// Geometric altitude and azimuth of the Sun from suncalc's low precision formulas, with
+/// the azimuth measured from north like every other `HzCoordinates`
 pub fn getSunPosition(julian: f64, lat: f64, lng: f64) -> HzCoordinates {
-    let lw = RAD * -lng;
     let lat_rad = RAD * lat;
-    let d = julian_epoch_offset(julian);
+    let date = JulianDate::utc(julian);
 
-    let sun_coords = sun_coords(d);
-    let hour_angle = siderealTime(d, lw) - sun_coords.ra;
+    let sun_coords = sun_coords(julian_epoch_offset(date.to_tt().jd));
+    let lst = (sidereal::gmst(date.to_ut1().jd) + Degrees(lng)).to_radians();
+    let hour_angle = (lst - sun_coords.ra).0;
 
     return HzCoordinates {
-        az: azimuth(hour_angle, lat_rad, sun_coords.dec),
-        alt: altitude(hour_angle, lat_rad, sun_coords.dec),
+        az: (Radians(azimuth(hour_angle, lat_rad, sun_coords.dec.0)).to_degrees() + Degrees(180.0)).normalized(),
+        alt: Radians(altitude(hour_angle, lat_rad, sun_coords.dec.0)).to_degrees(),
+    };
+}
+
+pub const SUNRISE_ALTITUDE: f64 = -0.833;
+pub const CIVIL_TWILIGHT_ALTITUDE: f64 = -6.0;
+pub const NAUTICAL_TWILIGHT_ALTITUDE: f64 = -12.0;
+pub const ASTRONOMICAL_TWILIGHT_ALTITUDE: f64 = -18.0;
+
+/// Returns solar noon, nadir, sunrise/sunset and twilight times for the day containing `julian`
+///
+/// Sunrise and sunset allow for the location's atmosphere and for the dip of the horizon
+/// seen from its elevation; twilight limits are fixed solar altitudes.
+///
+/// # Arguments
+///
+/// * `julian` - Julian day
+/// * `location` - Observer location
+///
+/// # Example
+///
+/// ```
+/// use astral::sun;
+/// use astral::coords::{Location, RiseSet};
+/// let location = Location::new(78.22, 15.65).unwrap();
+/// let times = sun::get_times(2458290.5, location);
+/// assert_eq!(times.sun, RiseSet::AlwaysUp);
+/// ```
+pub fn get_times(julian: f64, location: Location) -> SunTimes {
+    let lw = RAD * -location.lon;
+    let phi = RAD * location.lat;
+    let d = julian_epoch_offset(julian);
+
+    let n = julianCycle(d, lw);
+    let ds = approxTransit(0.0, lw, n);
+    // the transit is counted in UT, the Sun's motion in TT
+    let M = solar_mean_anomaly(ds + time::delta_t(julian) / 86400.0);
+    let L = ecliptic_longitude(M);
+    let dec = declination(L, 0.0);
+    let noon = solarTransitJ(ds, M, L);
+
+    let rise_set = |h: f64| -> RiseSet {
+        let h = RAD * h;
+        let cos_w = cosHourAngle(h, phi, dec);
+        if cos_w > 1.0 {
+            return RiseSet::AlwaysDown;
+        }
+        if cos_w < -1.0 {
+            return RiseSet::AlwaysUp;
+        }
+        let set = getSetJ(h, lw, phi, dec, n, M, L);
+        RiseSet::Times {
+            rise: noon - (set - noon),
+            set,
+        }
     };
+
+    SunTimes {
+        solar_noon: noon,
+        nadir: noon - 0.5,
+        sun: rise_set(
+            SUNRISE_ALTITUDE - HORIZON_REFRACTION * (location.refraction_scale() - 1.0) -
+                location.horizon_dip(),
+        ),
+        civil: rise_set(CIVIL_TWILIGHT_ALTITUDE),
+        nautical: rise_set(NAUTICAL_TWILIGHT_ALTITUDE),
+        astronomical: rise_set(ASTRONOMICAL_TWILIGHT_ALTITUDE),
+    }
 }
@@ -0,0 +1,465 @@
+//! Time scales and the conversions between them.
+//!
+//! Plain `f64` Julian days everywhere in the crate are UTC, as returned by
+//! `util::to_julian`. Before 1972, when UTC did not exist, they are taken as UT1. The
+//! ephemerides are evaluated in TT or TDB and sidereal time in UT1, converted from there
+//! with the functions below.
+use std::sync::RwLock;
+use chrono::{DateTime, TimeZone};
+use util;
+
+const SECONDS_IN_DAY: f64 = 86400.0;
+/// TT - TAI, seconds
+const TT_MINUS_TAI: f64 = 32.184;
+/// Julian day of the origin of Modified Julian Dates, 1858 November 17, 0h
+pub const MJD_EPOCH: f64 = 2400000.5;
+/// First day of the Gregorian calendar, 1582 October 15
+const GREGORIAN_REFORM: f64 = 2299160.5;
+/// Start of the leap second table, 1972 January 1
+const UTC_EPOCH: f64 = 2441317.5;
+/// Last date up to which the leap second table is known to be complete, 2027 January 1
+/// (IERS Bulletin C). `delta_t` extrapolates past it with the Espenak-Meeus polynomial.
+pub const LEAP_SECONDS_EXPIRE: f64 = 2461406.5;
+
+/// Julian day (0h UTC) from which each value of TAI - UTC in seconds applies
+pub const LEAP_SECONDS: [(f64, f64); 28] = [
+    (2441317.5, 10.0), // 1972 Jan 1
+    (2441499.5, 11.0), // 1972 Jul 1
+    (2441683.5, 12.0), // 1973 Jan 1
+    (2442048.5, 13.0), // 1974 Jan 1
+    (2442413.5, 14.0), // 1975 Jan 1
+    (2442778.5, 15.0), // 1976 Jan 1
+    (2443144.5, 16.0), // 1977 Jan 1
+    (2443509.5, 17.0), // 1978 Jan 1
+    (2443874.5, 18.0), // 1979 Jan 1
+    (2444239.5, 19.0), // 1980 Jan 1
+    (2444786.5, 20.0), // 1981 Jul 1
+    (2445151.5, 21.0), // 1982 Jul 1
+    (2445516.5, 22.0), // 1983 Jul 1
+    (2446247.5, 23.0), // 1985 Jul 1
+    (2447161.5, 24.0), // 1988 Jan 1
+    (2447892.5, 25.0), // 1990 Jan 1
+    (2448257.5, 26.0), // 1991 Jan 1
+    (2448804.5, 27.0), // 1992 Jul 1
+    (2449169.5, 28.0), // 1993 Jul 1
+    (2449534.5, 29.0), // 1994 Jul 1
+    (2450083.5, 30.0), // 1996 Jan 1
+    (2450630.5, 31.0), // 1997 Jul 1
+    (2451179.5, 32.0), // 1999 Jan 1
+    (2453736.5, 33.0), // 2006 Jan 1
+    (2454832.5, 34.0), // 2009 Jan 1
+    (2456109.5, 35.0), // 2012 Jul 1
+    (2457204.5, 36.0), // 2015 Jul 1
+    (2457754.5, 37.0), // 2017 Jan 1
+];
+
+lazy_static! {
+    static ref DELTA_T_TABLE: RwLock<DeltaT> = RwLock::new(DeltaT::default());
+}
+
+#[derive(Serialize,Deserialize,Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TimeScale {
+    /// Coordinated Universal Time, the civil time scale
+    Utc,
+    /// Terrestrial Time, the time scale of geocentric ephemerides
+    Tt,
+    /// Barycentric Dynamical Time, the time argument of VSOP87
+    Tdb,
+    /// Universal Time, following the rotation of the Earth
+    Ut1,
+}
+
+/// A Julian day tagged with the time scale it is counted in
+///
+/// # Example
+///
+/// ```
+/// use astral::time::{JulianDate, TimeScale};
+/// let utc = JulianDate::utc(2458061.5); // 2017 November 5, 0h UTC
+/// let tt = utc.to_tt();
+/// assert_eq!(tt.scale, TimeScale::Tt);
+/// assert!(((tt.jd - utc.jd) * 86400.0 - 69.184).abs() < 1e-4);
+/// assert!((tt.to_utc().jd - utc.jd).abs() < 1e-9);
+/// ```
+#[derive(Serialize,Deserialize,Debug, Clone, Copy, PartialEq)]
+pub struct JulianDate {
+    pub jd: f64,
+    pub scale: TimeScale,
+}
+
+impl JulianDate {
+    pub fn new(jd: f64, scale: TimeScale) -> JulianDate {
+        JulianDate {
+            jd,
+            scale,
+        }
+    }
+    pub fn utc(jd: f64) -> JulianDate {
+        JulianDate::new(jd, TimeScale::Utc)
+    }
+    pub fn tt(jd: f64) -> JulianDate {
+        JulianDate::new(jd, TimeScale::Tt)
+    }
+    pub fn tdb(jd: f64) -> JulianDate {
+        JulianDate::new(jd, TimeScale::Tdb)
+    }
+    pub fn ut1(jd: f64) -> JulianDate {
+        JulianDate::new(jd, TimeScale::Ut1)
+    }
+
+    /// The same instant counted in another time scale, with ΔT from the process-wide
+    /// table (see `set_delta_t_table`)
+    pub fn to_scale(&self, scale: TimeScale) -> JulianDate {
+        return self.to_scale_with(scale, &DELTA_T_TABLE.read().unwrap());
+    }
+    /// The same instant counted in another time scale, with ΔT from `delta_t`
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use astral::time::{DeltaT, JulianDate, TimeScale};
+    /// let observed = DeltaT::from_table(&[(1900.0, -2.0), (1901.0, -1.0)]);
+    /// let ut1 = JulianDate::ut1(2415203.125); // 1900 July 2
+    /// let tt = ut1.to_scale_with(TimeScale::Tt, &observed);
+    /// assert!(((tt.jd - ut1.jd) * 86400.0 + 1.5).abs() < 0.01);
+    /// ```
+    pub fn to_scale_with(&self, scale: TimeScale, delta_t: &DeltaT) -> JulianDate {
+        if scale == self.scale {
+            return *self;
+        }
+        let tt = match self.scale {
+            TimeScale::Tt => self.jd,
+            TimeScale::Utc => tt_frm_utc(self.jd, delta_t),
+            TimeScale::Ut1 => self.jd + delta_t.at(self.jd) / SECONDS_IN_DAY,
+            TimeScale::Tdb => self.jd - tdb_minus_tt(self.jd) / SECONDS_IN_DAY,
+        };
+        let jd = match scale {
+            TimeScale::Tt => tt,
+            TimeScale::Utc => utc_frm_tt(tt, delta_t),
+            // ΔT follows the leap seconds, so look it up on the UTC date
+            TimeScale::Ut1 => tt - delta_t.at(utc_frm_tt(tt, delta_t)) / SECONDS_IN_DAY,
+            TimeScale::Tdb => tt + tdb_minus_tt(tt) / SECONDS_IN_DAY,
+        };
+        JulianDate::new(jd, scale)
+    }
+    pub fn to_utc(&self) -> JulianDate {
+        self.to_scale(TimeScale::Utc)
+    }
+    pub fn to_tt(&self) -> JulianDate {
+        self.to_scale(TimeScale::Tt)
+    }
+    pub fn to_tdb(&self) -> JulianDate {
+        self.to_scale(TimeScale::Tdb)
+    }
+    pub fn to_ut1(&self) -> JulianDate {
+        self.to_scale(TimeScale::Ut1)
+    }
+
+    pub fn from_mjd(mjd: f64, scale: TimeScale) -> JulianDate {
+        JulianDate::new(julian_frm_mjd(mjd), scale)
+    }
+    /// Modified Julian Date in the same time scale
+    pub fn mjd(&self) -> f64 {
+        mjd_frm_julian(self.jd)
+    }
+}
+
+/// Bare Julian days are UTC
+impl From<f64> for JulianDate {
+    fn from(jd: f64) -> JulianDate {
+        JulianDate::utc(jd)
+    }
+}
+
+impl<Tz: TimeZone> From<DateTime<Tz>> for JulianDate {
+    fn from(date: DateTime<Tz>) -> JulianDate {
+        JulianDate::utc(util::to_julian(date))
+    }
+}
+
+/// Which calendar a year, month and day are counted in
+#[derive(Serialize,Deserialize,Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Calendar {
+    /// The Julian calendar, also before its introduction in 45 BC
+    Julian,
+    /// The Gregorian calendar, also before 1582
+    Gregorian,
+    /// The Julian calendar up to 1582 October 4 and the Gregorian calendar from the next
+    /// day, 1582 October 15, as in most historical records
+    Mixed,
+}
+
+/// Julian day of a calendar date (Meeus, ch. 7). Years are astronomical: 1 BC is year 0.
+///
+/// # Example
+///
+/// ```
+/// use astral::time::{self, Calendar};
+/// assert_eq!(time::julian_frm_calendar(1957, 10, 4.81, Calendar::Mixed), 2436116.31);
+/// assert_eq!(time::julian_frm_calendar(333, 1, 27.5, Calendar::Mixed), 1842713.0);
+/// assert_eq!(time::julian_frm_calendar(-4712, 1, 1.5, Calendar::Julian), 0.0);
+/// // the day after 1582 October 4 (Julian) is October 15 (Gregorian)
+/// assert_eq!(time::julian_frm_calendar(1582, 10, 4.0, Calendar::Mixed) + 1.0,
+///            time::julian_frm_calendar(1582, 10, 15.0, Calendar::Mixed));
+/// ```
+pub fn julian_frm_calendar(year: i32, month: u32, day: f64, calendar: Calendar) -> f64 {
+    let (y, m) = if month > 2 {
+        (year as f64, month as f64)
+    } else {
+        (year as f64 - 1.0, month as f64 + 12.0)
+    };
+    let julian = (365.25 * (y + 4716.0)).floor() + (30.6001 * (m + 1.0)).floor() + day - 1524.5;
+    let gregorian = match calendar {
+        Calendar::Julian => false,
+        Calendar::Gregorian => true,
+        Calendar::Mixed => (year, month, day) >= (1582, 10, 15.0),
+    };
+    if !gregorian {
+        return julian;
+    }
+    let a = (y / 100.0).floor();
+    julian + 2.0 - a + (a / 4.0).floor()
+}
+
+/// Year, month and day with its fraction of a Julian day (Meeus, ch. 7), for Julian
+/// days from 0
+///
+/// # Example
+///
+/// ```
+/// use astral::time::{self, Calendar};
+/// let (year, month, day) = time::calendar_frm_julian(2436116.31, Calendar::Mixed);
+/// assert_eq!((year, month), (1957, 10));
+/// assert!((day - 4.81).abs() < 1e-6);
+/// assert_eq!(time::calendar_frm_julian(1842713.0, Calendar::Mixed), (333, 1, 27.5));
+/// assert_eq!(time::calendar_frm_julian(1842713.0, Calendar::Gregorian), (333, 1, 28.5));
+/// ```
+pub fn calendar_frm_julian(julian: f64, calendar: Calendar) -> (i32, u32, f64) {
+    let z = (julian + 0.5).floor();
+    let f = julian + 0.5 - z;
+    let gregorian = match calendar {
+        Calendar::Julian => false,
+        Calendar::Gregorian => true,
+        Calendar::Mixed => z >= GREGORIAN_REFORM + 0.5,
+    };
+    let a = if gregorian {
+        let alpha = ((z - 1867216.25) / 36524.25).floor();
+        z + 1.0 + alpha - (alpha / 4.0).floor()
+    } else {
+        z
+    };
+    let b = a + 1524.0;
+    let c = ((b - 122.1) / 365.25).floor();
+    let d = (365.25 * c).floor();
+    let e = ((b - d) / 30.6001).floor();
+    let day = b - d - (30.6001 * e).floor() + f;
+    let month = if e < 14.0 { e - 1.0 } else { e - 13.0 };
+    let year = if month > 2.0 { c - 4716.0 } else { c - 4715.0 };
+    (year as i32, month as u32, day)
+}
+
+/// Modified Julian Date of a Julian day
+pub fn mjd_frm_julian(julian: f64) -> f64 {
+    julian - MJD_EPOCH
+}
+
+/// Julian day of a Modified Julian Date
+///
+/// # Example
+///
+/// ```
+/// use astral::time::{self, JulianDate, TimeScale};
+/// assert_eq!(time::julian_frm_mjd(58061.0), 2458061.5);
+/// assert_eq!(JulianDate::from_mjd(51544.5, TimeScale::Tt).jd, 2451545.0);
+/// ```
+pub fn julian_frm_mjd(mjd: f64) -> f64 {
+    mjd + MJD_EPOCH
+}
+
+/// TAI - UTC in seconds, or `None` before 1972
+pub fn tai_minus_utc(jd_utc: f64) -> Option<f64> {
+    LEAP_SECONDS
+        .iter()
+        .rev()
+        .find(|&&(start, _)| jd_utc >= start)
+        .map(|&(_, seconds)| seconds)
+}
+
+/// ΔT = TT - UT1 from a table of (decimal year, ΔT in seconds) pairs, interpolated
+/// linearly between entries, and from the built-in model outside of it. The default
+/// value has no entries and is the model alone.
+///
+/// # Example
+///
+/// ```
+/// use astral::time::DeltaT;
+/// let observed = DeltaT::from_table(&[(2017.0, 68.6), (2018.0, 68.9)]);
+/// assert!((observed.at(2458119.5 - 182.6) - 68.75).abs() < 0.01);
+/// assert_eq!(DeltaT::default().at(2458119.5 - 182.6), 69.184);
+/// ```
+#[derive(Serialize,Deserialize,Debug, Clone, PartialEq, Default)]
+pub struct DeltaT {
+    entries: Vec<(f64, f64)>,
+}
+
+impl DeltaT {
+    pub fn from_table(table: &[(f64, f64)]) -> DeltaT {
+        let mut entries = table.to_vec();
+        entries.sort_by(|a, b| a.0.total_cmp(&b.0));
+        DeltaT { entries }
+    }
+
+    /// ΔT in seconds at the Julian day `julian`
+    ///
+    /// Taken from the table when it covers the date. Otherwise, from 1972 until
+    /// `LEAP_SECONDS_EXPIRE`, from the leap seconds, UT1 being kept within 0.9 s of UTC;
+    /// at other times from the polynomials of Espenak and Meeus (2006), joined
+    /// continuously onto the leap second value after the table expires.
+    pub fn at(&self, julian: f64) -> f64 {
+        let year = decimal_year(julian);
+        if let Some(seconds) = self.interpolate(year) {
+            return seconds;
+        }
+        if (UTC_EPOCH..LEAP_SECONDS_EXPIRE).contains(&julian) {
+            return TT_MINUS_TAI + tai_minus_utc(julian).unwrap();
+        }
+        if julian >= LEAP_SECONDS_EXPIRE {
+            let expire_year = decimal_year(LEAP_SECONDS_EXPIRE);
+            let last = TT_MINUS_TAI + tai_minus_utc(LEAP_SECONDS_EXPIRE).unwrap();
+            return last + polynomial_delta_t(year) - polynomial_delta_t(expire_year);
+        }
+        polynomial_delta_t(year)
+    }
+
+    fn interpolate(&self, year: f64) -> Option<f64> {
+        let table = &self.entries;
+        if table.is_empty() || year < table[0].0 || year > table[table.len() - 1].0 {
+            return None;
+        }
+        for pair in table.windows(2) {
+            let ((y0, dt0), (y1, dt1)) = (pair[0], pair[1]);
+            if year <= y1 {
+                return Some(dt0 + (dt1 - dt0) * (year - y0) / (y1 - y0));
+            }
+        }
+        Some(table[0].1)
+    }
+}
+
+/// ΔT = TT - UT1 in seconds, from the process-wide table (see `DeltaT::at`)
+///
+/// # Example
+///
+/// ```
+/// use astral::time;
+/// assert_eq!(time::delta_t(2458061.5), 69.184); // 2017
+/// assert!((time::delta_t(2415020.5) + 2.7).abs() < 0.5); // 1900
+/// assert!((time::delta_t(2305447.5) - 120.0).abs() < 1.0); // 1600
+/// ```
+pub fn delta_t(julian: f64) -> f64 {
+    return DELTA_T_TABLE.read().unwrap().at(julian);
+}
+
+/// Sets the ΔT table used by every conversion of the crate that is not given a `DeltaT`
+/// of its own, for the whole process and every thread in it: all positions, rise and set
+/// times and events computed afterwards change with it. To use a table for some
+/// computations only, pass it to `JulianDate::to_scale_with` instead.
+pub fn set_delta_t_table(table: &[(f64, f64)]) {
+    *DELTA_T_TABLE.write().unwrap() = DeltaT::from_table(table);
+}
+
+/// Goes back to the built-in ΔT model for the whole process
+pub fn clear_delta_t_table() {
+    *DELTA_T_TABLE.write().unwrap() = DeltaT::default();
+}
+
+/// TDB - TT in seconds, the main periodic terms (Fairhead & Bretagnon)
+pub fn tdb_minus_tt(julian: f64) -> f64 {
+    let g = (357.53 + 0.98560028 * (julian - 2451545.0)).to_radians();
+    0.001657 * g.sin() + 0.000014 * (2.0 * g).sin()
+}
+
+fn tt_frm_utc(jd_utc: f64, delta_t: &DeltaT) -> f64 {
+    match tai_minus_utc(jd_utc) {
+        Some(seconds) => jd_utc + (seconds + TT_MINUS_TAI) / SECONDS_IN_DAY,
+        None => jd_utc + delta_t.at(jd_utc) / SECONDS_IN_DAY,
+    }
+}
+
+fn utc_frm_tt(jd_tt: f64, delta_t: &DeltaT) -> f64 {
+    // the offset depends on the UTC date sought, so start from the one at the TT date
+    let mut utc = jd_tt;
+    for _ in 0..3 {
+        utc = jd_tt - (tt_frm_utc(utc, delta_t) - utc);
+    }
+    utc
+}
+
+fn decimal_year(julian: f64) -> f64 {
+    2000.0 + (julian - 2451545.0) / 365.25
+}
+
+/// ΔT in seconds from the polynomials of Espenak and Meeus (2006), NASA Five Millennium
+/// Canon of Solar Eclipses
+fn polynomial_delta_t(y: f64) -> f64 {
+    if y < -500.0 {
+        let u = (y - 1820.0) / 100.0;
+        return -20.0 + 32.0 * u * u;
+    }
+    if y < 500.0 {
+        let u = y / 100.0;
+        return 10583.6 +
+            u * (-1014.41 + u * (33.78311 + u * (-5.952053 + u * (-0.1798452 + u * (0.022174192 + u * 0.0090316521)))));
+    }
+    if y < 1600.0 {
+        let u = (y - 1000.0) / 100.0;
+        return 1574.2 +
+            u * (-556.01 + u * (71.23472 + u * (0.319781 + u * (-0.8503463 + u * (-0.005050998 + u * 0.0083572073)))));
+    }
+    if y < 1700.0 {
+        let t = y - 1600.0;
+        return 120.0 + t * (-0.9808 + t * (-0.01532 + t / 7129.0));
+    }
+    if y < 1800.0 {
+        let t = y - 1700.0;
+        return 8.83 + t * (0.1603 + t * (-0.0059285 + t * (0.00013336 - t / 1174000.0)));
+    }
+    if y < 1860.0 {
+        let t = y - 1800.0;
+        return 13.72 +
+            t * (-0.332447 + t * (0.0068612 + t * (0.0041116 + t * (-0.00037436 + t * (0.0000121272 + t * (-0.0000001699 + t * 0.000000000875))))));
+    }
+    if y < 1900.0 {
+        let t = y - 1860.0;
+        return 7.62 + t * (0.5737 + t * (-0.251754 + t * (0.01680668 + t * (-0.0004473624 + t / 233174.0))));
+    }
+    if y < 1920.0 {
+        let t = y - 1900.0;
+        return -2.79 + t * (1.494119 + t * (-0.0598939 + t * (0.0061966 - t * 0.000197)));
+    }
+    if y < 1941.0 {
+        let t = y - 1920.0;
+        return 21.20 + t * (0.84493 + t * (-0.076100 + t * 0.0020936));
+    }
+    if y < 1961.0 {
+        let t = y - 1950.0;
+        return 29.07 + t * (0.407 + t * (-1.0 / 233.0 + t / 2547.0));
+    }
+    if y < 1986.0 {
+        let t = y - 1975.0;
+        return 45.45 + t * (1.067 + t * (-1.0 / 260.0 - t / 718.0));
+    }
+    if y < 2005.0 {
+        let t = y - 2000.0;
+        return 63.86 + t * (0.3345 + t * (-0.060374 + t * (0.0017275 + t * (0.000651814 + t * 0.00002373599))));
+    }
+    if y < 2050.0 {
+        let t = y - 2000.0;
+        return 62.92 + t * (0.32217 + t * 0.005589);
+    }
+    let u = (y - 1820.0) / 100.0;
+    if y < 2150.0 {
+        return -20.0 + 32.0 * u * u - 0.5628 * (2150.0 - y);
+    }
+    -20.0 + 32.0 * u * u
+}
@@ -0,0 +1,191 @@
+//! Angle units. Angles in the coordinate types carry their unit in their type, so that
+//! passing degrees where radians are expected is a compile error; `.0` is the bare value.
+use std::f64::consts::PI;
+use std::ops::{Add, AddAssign, Div, Mul, Neg, Sub, SubAssign};
+
+/// An angle in degrees
+///
+/// # Example
+///
+/// ```
+/// use astral::units::Degrees;
+/// assert_eq!(Degrees(-30.0).normalized(), Degrees(330.0));
+/// assert_eq!(Degrees(350.0).signed(), Degrees(-10.0));
+/// assert!((Degrees(90.0).to_radians().0 - std::f64::consts::FRAC_PI_2).abs() < 1e-15);
+/// ```
+#[derive(Serialize,Deserialize,Debug, Clone, Copy, PartialEq, PartialOrd, Default)]
+pub struct Degrees(pub f64);
+
+/// An angle in radians
+#[derive(Serialize,Deserialize,Debug, Clone, Copy, PartialEq, PartialOrd, Default)]
+pub struct Radians(pub f64);
+
+/// An angle in hours of time, 15° each, as used for right ascension and hour angles
+#[derive(Serialize,Deserialize,Debug, Clone, Copy, PartialEq, PartialOrd, Default)]
+pub struct HourAngle(pub f64);
+
+/// `value` reduced into [0, `turn`)
+fn wrap(value: f64, turn: f64) -> f64 {
+    let wrapped = value - turn * (value / turn).floor();
+    // rounding can land a tiny negative value on `turn` itself
+    if wrapped < turn { wrapped } else { 0.0 }
+}
+
+macro_rules! angle_ops {
+    ($unit: ident, $turn: expr) => {
+        impl $unit {
+            /// The same angle within [0, one turn)
+            pub fn normalized(self) -> $unit {
+                return $unit(wrap(self.0, $turn));
+            }
+            /// The same angle within [-half a turn, half a turn)
+            pub fn signed(self) -> $unit {
+                return $unit(wrap(self.0 + $turn / 2.0, $turn) - $turn / 2.0);
+            }
+            pub fn abs(self) -> $unit {
+                return $unit(self.0.abs());
+            }
+            pub fn sin(self) -> f64 {
+                return Radians::from(self).0.sin();
+            }
+            pub fn cos(self) -> f64 {
+                return Radians::from(self).0.cos();
+            }
+            pub fn tan(self) -> f64 {
+                return Radians::from(self).0.tan();
+            }
+        }
+        impl Add for $unit {
+            type Output = $unit;
+            fn add(self, other: $unit) -> $unit {
+                return $unit(self.0 + other.0);
+            }
+        }
+        impl Sub for $unit {
+            type Output = $unit;
+            fn sub(self, other: $unit) -> $unit {
+                return $unit(self.0 - other.0);
+            }
+        }
+        impl AddAssign for $unit {
+            fn add_assign(&mut self, other: $unit) {
+                self.0 += other.0;
+            }
+        }
+        impl SubAssign for $unit {
+            fn sub_assign(&mut self, other: $unit) {
+                self.0 -= other.0;
+            }
+        }
+        impl Neg for $unit {
+            type Output = $unit;
+            fn neg(self) -> $unit {
+                return $unit(-self.0);
+            }
+        }
+        impl Mul<f64> for $unit {
+            type Output = $unit;
+            fn mul(self, factor: f64) -> $unit {
+                return $unit(self.0 * factor);
+            }
+        }
+        impl Div<f64> for $unit {
+            type Output = $unit;
+            fn div(self, divisor: f64) -> $unit {
+                return $unit(self.0 / divisor);
+            }
+        }
+    };
+}
+
+angle_ops!(Degrees, 360.0);
+angle_ops!(Radians, 2.0 * PI);
+angle_ops!(HourAngle, 24.0);
+
+impl Degrees {
+    pub fn to_radians(self) -> Radians {
+        Radians(self.0.to_radians())
+    }
+    pub fn to_hours(self) -> HourAngle {
+        HourAngle(self.0 / 15.0)
+    }
+}
+
+impl Radians {
+    pub fn to_degrees(self) -> Degrees {
+        Degrees(self.0.to_degrees())
+    }
+    pub fn to_hours(self) -> HourAngle {
+        self.to_degrees().to_hours()
+    }
+}
+
+impl HourAngle {
+    pub fn to_degrees(self) -> Degrees {
+        Degrees(self.0 * 15.0)
+    }
+    pub fn to_radians(self) -> Radians {
+        self.to_degrees().to_radians()
+    }
+    /// Whole hours, whole minutes and seconds, signed by the hours (or, under an hour, by
+    /// the first non-zero part)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use astral::units::{Degrees, HourAngle};
+    /// let (h, m, s) = Degrees(37.95).to_hours().hms();
+    /// assert_eq!((h, m), (2, 31));
+    /// assert!((s - 48.0).abs() < 1e-9);
+    /// assert_eq!(HourAngle(-1.5).hms(), (-1, 30, 0.0));
+    /// ```
+    pub fn hms(self) -> (i32, i32, f64) {
+        let sign = if self.0 < 0.0 { -1 } else { 1 };
+        let total = self.0.abs() * 3600.0;
+        let (hours, minutes) = ((total / 3600.0).floor() as i32, ((total % 3600.0) / 60.0).floor() as i32);
+        let seconds = total % 60.0;
+        if hours != 0 {
+            return (sign * hours, minutes, seconds);
+        }
+        if minutes != 0 {
+            return (0, sign * minutes, seconds);
+        }
+        (0, 0, sign as f64 * seconds)
+    }
+}
+
+impl From<Radians> for Degrees {
+    fn from(angle: Radians) -> Degrees {
+        angle.to_degrees()
+    }
+}
+
+impl From<HourAngle> for Degrees {
+    fn from(angle: HourAngle) -> Degrees {
+        angle.to_degrees()
+    }
+}
+
+impl From<Degrees> for Radians {
+    fn from(angle: Degrees) -> Radians {
+        angle.to_radians()
+    }
+}
+
+impl From<HourAngle> for Radians {
+    fn from(angle: HourAngle) -> Radians {
+        angle.to_radians()
+    }
+}
+
+impl From<Degrees> for HourAngle {
+    fn from(angle: Degrees) -> HourAngle {
+        angle.to_hours()
+    }
+}
+
+impl From<Radians> for HourAngle {
+    fn from(angle: Radians) -> HourAngle {
+        angle.to_hours()
+    }
+}
@@ -1,5 +1,6 @@
 use super::*;
 use coords::*;
+use units::{Degrees, Radians};
 use std::f64::consts::PI;
 #[macro_export]
 macro_rules! eq_frm_ecl {
@@ -11,29 +12,52 @@ macro_rules! eq_frm_ecl {
 
 pub const MILLLISECONDS_IN_DAY: f64 = 1000.0 * 60.0 * 60.0 * 24.0;
 pub const J1970: f64 = 2440588.0;
+const SECONDS_IN_DAY: i64 = 86400;
 pub const J2000: f64 = 2451545.0;
 pub const RAD: f64 = PI / 180.0;
+pub const AU_IN_KM: f64 = 149597870.7;
+/// One AU in parsecs, the unit of star distances
+pub const AU_IN_PARSECS: f64 = 1.0 / 206264.806;
+/// Speed of light in AU per day
+pub const SPEED_OF_LIGHT: f64 = 173.1446326846693;
+pub const EARTH_RADIUS_IN_KM: f64 = 6378.14;
+/// Standard atmosphere used by refraction formulas: millibars and degrees Celsius
+pub const STANDARD_PRESSURE: f64 = 1010.0;
+pub const STANDARD_TEMPERATURE: f64 = 10.0;
+/// Refraction at the horizon in the standard atmosphere, degrees
+pub const HORIZON_REFRACTION: f64 = 0.5667;
 pub const OBLIQUITY_OF_EARTH: f64 = RAD * 23.4397;
-// const J0: f64 = 0.0009;
-// fn julianCycle(d: f64, lw: f64) -> f64 {
-//     return (d - J0 - lw / (2.0 * PI)).round();
-// }
-
-// fn approxTransit(Ht: f64, lw: f64, n: f64) -> f64 { return J0 + (Ht + lw) / (2.0 * PI) + n; }
-// fn solarTransitJ(ds: f64, M: f64, L: f64) -> f64 {
-//     return J2000 + ds + 0.0053 * M.sin() - 0.0069 * (2.0 * L).sin();
-// }
-
-// fn hourAngle(h: f64, phi: f64, d: f64) -> f64 {
-//     return ((h.sin() - phi.sin() * d.sin()) / (phi.cos() * d.cos())).acos();
-// }
-
-// // // returns set time for the given sun altitude
-// fn getSetJ(h: f64, lw: f64, phi: f64, dec: f64, n: f64, M: f64, L: f64) -> f64 {
-//     let w = hourAngle(h, phi, dec);
-//     let a = approxTransit(w, lw, n);
-//     return solarTransitJ(a, M, L);
-// }
+pub const J0: f64 = 0.0009;
+
+pub fn julianCycle(d: f64, lw: f64) -> f64 {
+    (d - J0 - lw / (2.0 * PI)).round()
+}
+
+pub fn approxTransit(Ht: f64, lw: f64, n: f64) -> f64 {
+    J0 + (Ht + lw) / (2.0 * PI) + n
+}
+
+pub fn solarTransitJ(ds: f64, M: f64, L: f64) -> f64 {
+    J2000 + ds + 0.0053 * M.sin() - 0.0069 * (2.0 * L).sin()
+}
+
+/// Cosine of the hour angle at which a body of declination `d` reaches altitude `h`
+/// for an observer at latitude `phi` (all in radians). Values outside [-1, 1] mean
+/// the body never crosses that altitude.
+pub fn cosHourAngle(h: f64, phi: f64, d: f64) -> f64 {
+    (h.sin() - phi.sin() * d.sin()) / (phi.cos() * d.cos())
+}
+
+pub fn hourAngle(h: f64, phi: f64, d: f64) -> f64 {
+    cosHourAngle(h, phi, d).acos()
+}
+
+// returns set time for the given sun altitude
+pub fn getSetJ(h: f64, lw: f64, phi: f64, dec: f64, n: f64, M: f64, L: f64) -> f64 {
+    let w = hourAngle(h, phi, dec);
+    let a = approxTransit(w, lw, n);
+    solarTransitJ(a, M, L)
+}
 
 pub fn geo_frm_helio(julian_day: f64, helio_coords: CartesianCoordinates) -> CartesianCoordinates {
     let earth_coords = vsop87::vsop87c::earth(julian_day);
@@ -44,12 +68,75 @@ pub fn geo_frm_helio(julian_day: f64, helio_coords: CartesianCoordinates) -> Car
     };
 }
 
+/// Heliocentric velocity of the Earth in AU per day (VSOP87C, ecliptic of date)
+pub fn earth_velocity(julian_day: f64) -> CartesianCoordinates {
+    let dt = 0.01;
+    let before = vsop87::vsop87c::earth(julian_day - dt);
+    let after = vsop87::vsop87c::earth(julian_day + dt);
+    CartesianCoordinates {
+        x: (after.x - before.x) / (2.0 * dt),
+        y: (after.y - before.y) / (2.0 * dt),
+        z: (after.z - before.z) / (2.0 * dt),
+    }
+}
+
+/// Geocentric position of an observer in AU, equatorial of date, at a UTC Julian day
+pub fn observer_eq_cart(julian_day: f64, location: Location) -> CartesianCoordinates {
+    let (rho_sin, rho_cos) =
+        astro::planet::earth::rho_sin_cos_phi(location.lat.to_radians(), location.elevation);
+    let lst = sidereal::local_sidereal_time(julian_day, location).to_radians().0;
+    let radius = EARTH_RADIUS_IN_KM / AU_IN_KM;
+    CartesianCoordinates {
+        x: radius * rho_cos * lst.cos(),
+        y: radius * rho_cos * lst.sin(),
+        z: radius * rho_sin,
+    }
+}
+
+pub fn norm(cart_coords: &CartesianCoordinates) -> f64 {
+    (cart_coords.x * cart_coords.x + cart_coords.y * cart_coords.y +
+        cart_coords.z * cart_coords.z)
+        .sqrt()
+}
+
 pub fn cart_frm_eq(eq_coords: EqCoordinates) -> CartesianCoordinates {
     let x = eq_coords.dist * eq_coords.dec.cos() * eq_coords.ra.cos();
     let y = eq_coords.dist * eq_coords.dec.cos() * eq_coords.ra.sin();
     let z = eq_coords.dist * eq_coords.dec.sin();
     return CartesianCoordinates { x: x, y: y, z: z };
 }
+/// Angle in radians between two vectors. Taken as the atan2 of the cross and dot
+/// products, so it stays accurate for nearly parallel (and antiparallel) vectors.
+pub fn angle_between(a: &CartesianCoordinates, b: &CartesianCoordinates) -> f64 {
+    let cross = CartesianCoordinates {
+        x: a.y * b.z - a.z * b.y,
+        y: a.z * b.x - a.x * b.z,
+        z: a.x * b.y - a.y * b.x,
+    };
+    let dot = a.x * b.x + a.y * b.y + a.z * b.z;
+    norm(&cross).atan2(dot)
+}
+/// Position angle in radians of the direction `b` seen from the direction `a`, measured
+/// from the north pole of their common equatorial frame through east, within [0, 2π)
+pub fn position_angle(a: &CartesianCoordinates, b: &CartesianCoordinates) -> f64 {
+    let ra = a.y.atan2(a.x);
+    let dec = a.z.atan2((a.x * a.x + a.y * a.y).sqrt());
+    let east = -ra.sin() * b.x + ra.cos() * b.y;
+    let north = -dec.sin() * ra.cos() * b.x - dec.sin() * ra.sin() * b.y + dec.cos() * b.z;
+    let angle = east.atan2(north);
+    if angle >= 0.0 { angle } else { 2.0 * PI + angle }
+}
+/// Angular separation of two equatorial positions
+pub fn angular_separation(a: &EqCoordinates, b: &EqCoordinates) -> Degrees {
+    let unit = |eq: &EqCoordinates| {
+        cart_frm_eq(EqCoordinates {
+            ra: eq.ra,
+            dec: eq.dec,
+            dist: 1.0,
+        })
+    };
+    Radians(angle_between(&unit(a), &unit(b))).to_degrees()
+}
 pub fn helio_frm_geo(julian_day: f64, geo_coords: CartesianCoordinates) -> CartesianCoordinates {
     let earth_coords = vsop87::vsop87c::earth(julian_day);
     return CartesianCoordinates {
@@ -60,38 +147,89 @@ pub fn helio_frm_geo(julian_day: f64, geo_coords: CartesianCoordinates) -> Carte
 }
 pub fn ecl_frm_cart(x: f64, y: f64, z: f64) -> EclCoordinates {
     let r = (x * x + y * y + z * z).sqrt();
-    let mut ra = (y).atan2(x);
-    ra = if ra >= 0.0 { ra } else { (2.0 * PI + ra) };
-    let dec = (z / r).asin();
     return EclCoordinates {
-        lat: dec,
-        lng: ra,
+        lat: Radians((z / r).asin()).to_degrees(),
+        lng: Radians(y.atan2(x)).to_degrees().normalized(),
         dist: r,
     };
 }
 pub fn eq_frm_cart(cart_coords: CartesianCoordinates) -> EqCoordinates {
     let (x, y, z) = (cart_coords.x, cart_coords.y, cart_coords.z);
     let r = (x * x + y * y + z * z).sqrt();
-    let mut ra = (y).atan2(x);
-    ra = if ra >= 0.0 { ra } else { (2.0 * PI + ra) };
-    let dec = (z / r).asin();
-    // let ra=(r).atan2(z);
-    // let dec=(y).atan2(x);
     return EqCoordinates {
-        dec: dec.to_degrees(),
-        ra: ra.to_degrees(),
+        dec: Radians((z / r).asin()).to_degrees(),
+        ra: Radians(y.atan2(x)).to_degrees().normalized(),
         dist: r,
     };
 }
 
-pub fn to_julian(date: DateTime<FixedOffset>) -> f64 {
-    let ts = (date.timestamp() as f64) * 1000.0;
-    return ts / MILLLISECONDS_IN_DAY - 0.5 + J1970;
+/// UTC Julian day of a date in any time zone; see `time` for the other time scales.
+/// An `f64` Julian day resolves about 40 microseconds today; `to_julian_parts` keeps
+/// every nanosecond.
+pub fn to_julian<Tz: TimeZone>(date: DateTime<Tz>) -> f64 {
+    let (day, fraction) = to_julian_parts(date);
+    day + fraction
+}
+
+/// UTC Julian day of a date split into the Julian day at the preceding 0h and the
+/// fraction of a day since, which together convert back with `from_julian_parts` to the
+/// same nanosecond
+///
+/// # Example
+///
+/// ```
+/// extern crate chrono;
+/// extern crate astral;
+/// use astral::util;
+/// use chrono::prelude::*;
+/// # fn main() {
+/// let tz = FixedOffset::west_opt(7 * 3600).unwrap();
+/// let date = tz.with_ymd_and_hms(2017, 11, 4, 11, 35, 1).unwrap() + chrono::Duration::nanoseconds(123456789);
+/// let (day, fraction) = util::to_julian_parts(date);
+/// assert_eq!(day, 2458061.5);
+/// assert_eq!(util::from_julian_parts(day, fraction).unwrap(), date);
+/// assert_eq!(util::from_julian_tz(util::to_julian(date), &tz).unwrap().hour(), 11);
+/// assert_eq!(util::from_julian(1e20), None);
+/// # }
+/// ```
+pub fn to_julian_parts<Tz: TimeZone>(date: DateTime<Tz>) -> (f64, f64) {
+    let seconds = date.timestamp();
+    let nanos = date.timestamp_subsec_nanos() as f64;
+    let days = seconds.div_euclid(SECONDS_IN_DAY);
+    let remainder = seconds.rem_euclid(SECONDS_IN_DAY) as f64;
+    (
+        J1970 - 0.5 + days as f64,
+        (remainder * 1e9 + nanos) / (SECONDS_IN_DAY as f64 * 1e9),
+    )
+}
+
+/// UTC date of a Julian day, to the nearest nanosecond the `f64` can carry, or `None`
+/// when it is not finite or lies beyond the dates chrono can represent (about 262,000
+/// years either side of 1970)
+pub fn from_julian(julian: f64) -> Option<DateTime<Utc>> {
+    from_julian_parts(julian, 0.0)
 }
 
-pub fn from_julian(julian: f64) -> NaiveDateTime {
-    let millis = ((julian + 0.5 - J1970) * MILLLISECONDS_IN_DAY) as i64 / 1000;
-    return NaiveDateTime::from_timestamp(millis, 0);
+/// Date of a UTC Julian day in the time zone `tz`, or `None` as for `from_julian`
+pub fn from_julian_tz<Tz: TimeZone>(julian: f64, tz: &Tz) -> Option<DateTime<Tz>> {
+    from_julian(julian).map(|date| date.with_timezone(tz))
+}
+
+/// UTC date of a Julian day given in two parts, usually as returned by `to_julian_parts`,
+/// or `None` as for `from_julian`
+pub fn from_julian_parts(day: f64, fraction: f64) -> Option<DateTime<Utc>> {
+    let since_epoch = day - (J1970 - 0.5);
+    let whole = since_epoch.floor();
+    let mut fraction = (since_epoch - whole) + fraction;
+    let days = whole + fraction.floor();
+    // well past chrono's range, but small enough for the arithmetic below not to overflow
+    if !days.is_finite() || days.abs() > 1e9 {
+        return None;
+    }
+    fraction -= fraction.floor();
+    let nanos = (fraction * SECONDS_IN_DAY as f64 * 1e9).round() as i64;
+    let seconds = days as i64 * SECONDS_IN_DAY + nanos.div_euclid(1_000_000_000);
+    Utc.timestamp_opt(seconds, nanos.rem_euclid(1_000_000_000) as u32).single()
 }
 
 pub fn julian_epoch_offset(julian: f64) -> f64 {
@@ -124,11 +262,36 @@ pub fn altitude(H: f64, phi: f64, dec: f64) -> f64 {
     return (phi.sin() * dec.sin() + phi.cos() * dec.cos() * H.cos()).asin();
 }
 
+/// Local mean sidereal time in radians, within [-lw, 2π - lw), for `d` days of UTC since
+/// J2000 and west longitude `lw` in radians, from `sidereal::gmst`
 pub fn siderealTime(d: f64, lw: f64) -> f64 {
-    return RAD * (280.16 + 360.9856235 * d) - lw;
+    sidereal::gmst(JulianDate::utc(d + J2000).to_ut1().jd).to_radians().0 - lw
 }
 
+/// Refraction (radians) for a true altitude `h` in radians, standard atmosphere
 pub fn astroRefraction(h: f64) -> f64 {
-    let alt = if h < 0.0 { 0.0 } else { h };
-    return 0.0002967 / (alt + 0.00312536 / (alt + 0.08901179)).tan();
+    refraction(h, STANDARD_PRESSURE, STANDARD_TEMPERATURE)
+}
+
+/// Refraction (radians) for a true altitude `h` in radians, with pressure in millibars and
+/// temperature in degrees Celsius (Saemundsson's formula). Altitudes below -1° use the
+/// value at -1°, where the formula stops being meaningful.
+pub fn refraction(h: f64, pressure: f64, temperature: f64) -> f64 {
+    let alt = h.max(-RAD);
+    // R = 1.02' / tan(h + 10.3 / (h + 5.11)), h and the tangent's argument in degrees (Meeus, 16.4)
+    refraction_scale(pressure, temperature) * 0.0002967 /
+        (alt + 10.3 * RAD * RAD / (alt + 5.11 * RAD)).tan()
+}
+
+/// Refraction relative to the standard atmosphere
+pub fn refraction_scale(pressure: f64, temperature: f64) -> f64 {
+    (pressure / STANDARD_PRESSURE) * ((273.0 + STANDARD_TEMPERATURE) / (273.0 + temperature))
+}
+
+/// Dip of the sea horizon (degrees) for an observer `elevation` meters above it
+pub fn horizon_dip(elevation: f64) -> f64 {
+    if elevation <= 0.0 {
+        return 0.0;
+    }
+    0.0293 * elevation.sqrt()
 }